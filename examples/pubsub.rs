@@ -0,0 +1,27 @@
+extern crate env_logger;
+extern crate futures;
+extern crate tokio_core;
+extern crate tokio_redis as redis;
+
+use futures::{Future, Stream};
+use tokio_core::reactor::Core;
+use redis::Client;
+
+pub fn main() {
+    env_logger::init().unwrap();
+
+    let addr = "127.0.0.1:6379".parse().unwrap();
+    let mut lp = Core::new().unwrap();
+
+    let res = Client::new().connect_pubsub(&addr, &lp.handle())
+        .and_then(|pubsub| pubsub.subscribe("news"))
+        .and_then(|pubsub| {
+            pubsub.for_each(|msg| {
+                let payload: String = msg.payload().unwrap();
+                println!("MESSAGE on {}: {}", String::from_utf8_lossy(msg.channel()), payload);
+                Ok(())
+            })
+        });
+
+    lp.run(res).unwrap();
+}
@@ -0,0 +1,166 @@
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures::Future;
+use tokio_core::reactor::Handle;
+
+use {Cmd, Client, ClientHandle, Response};
+use commands::{
+    counted_args,
+    millis,
+    xreadgroup_cmd,
+    Direction,
+    StreamKey,
+    XReadGroupOptions,
+    XReadOptions,
+};
+use types::{ToRedisArgs, FromRedisValue};
+
+/// A client for the blocking list, sorted set and stream commands.
+///
+/// Replies on a connection arrive in the order the requests were sent,
+/// so a `BLPOP` waiting for data delays every request pipelined behind
+/// it.  The blocking commands are therefore only offered on this handle,
+/// which owns a connection of its own.  Create it with
+/// `Client::connect_blocking` and keep using a regular `ClientHandle` for
+/// all other traffic.
+///
+/// A timeout of zero blocks until data is available.  When a timeout
+/// expires the commands resolve to `None`, or to no entries for the
+/// stream reads.
+///
+/// The popping commands return the name of the key the data came from.
+/// Key names are binary safe, so it is returned as bytes.
+pub struct BlockingHandle {
+    inner: ClientHandle,
+}
+
+/// Connect a client to be used for blocking commands only.
+pub fn connect(client: Client, addr: &SocketAddr, handle: &Handle)
+        -> Box<Future<Item = BlockingHandle, Error = io::Error>>
+{
+    let ret = client.connect(addr, handle)
+        .map(|inner| BlockingHandle { inner: inner });
+
+    Box::new(ret)
+}
+
+impl BlockingHandle {
+    /// Pop the first element of the first non-empty list, returning the
+    /// name of the list along with the element.
+    pub fn blpop<K, T>(&mut self, keys: K, timeout: Duration) -> Response<Option<(Vec<u8>, T)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BLPOP").arg(keys).arg(timeout_secs(timeout));
+
+        self.inner.query(cmd)
+    }
+
+    /// Pop the last element of the first non-empty list, returning the
+    /// name of the list along with the element.
+    pub fn brpop<K, T>(&mut self, keys: K, timeout: Duration) -> Response<Option<(Vec<u8>, T)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BRPOP").arg(keys).arg(timeout_secs(timeout));
+
+        self.inner.query(cmd)
+    }
+
+    /// Move an element from one end of `src` to one end of `dst`,
+    /// returning the element.
+    pub fn blmove<S, D, T>(&mut self, src: S, dst: D, from: Direction, to: Direction,
+                           timeout: Duration) -> Response<Option<T>>
+        where S: ToRedisArgs,
+              D: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BLMOVE").arg(src).arg(dst).arg(from).arg(to).arg(timeout_secs(timeout));
+
+        self.inner.query(cmd)
+    }
+
+    /// Pop up to `count` elements from the first non-empty list,
+    /// returning the name of the list along with the elements.
+    pub fn blmpop<K, T>(&mut self, keys: K, direction: Direction, count: usize,
+                        timeout: Duration) -> Response<Option<(Vec<u8>, Vec<T>)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BLMPOP").arg(timeout_secs(timeout));
+        counted_args(&mut cmd, keys);
+        cmd.arg(direction).arg("COUNT").arg(count);
+
+        self.inner.query(cmd)
+    }
+
+    /// Pop the member with the lowest score from the first non-empty
+    /// sorted set, returning the name of the set, the member and its
+    /// score.
+    pub fn bzpopmin<K, T>(&mut self, keys: K, timeout: Duration)
+            -> Response<Option<(Vec<u8>, T, f64)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BZPOPMIN").arg(keys).arg(timeout_secs(timeout));
+
+        self.inner.query(cmd)
+    }
+
+    /// Pop the member with the highest score from the first non-empty
+    /// sorted set, returning the name of the set, the member and its
+    /// score.
+    pub fn bzpopmax<K, T>(&mut self, keys: K, timeout: Duration)
+            -> Response<Option<(Vec<u8>, T, f64)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BZPOPMAX").arg(keys).arg(timeout_secs(timeout));
+
+        self.inner.query(cmd)
+    }
+
+    /// Read the entries following the given IDs from one or more streams,
+    /// waiting for new entries if there are none.  See
+    /// `ClientHandle::xread`.
+    pub fn xread<K, I>(&mut self, keys: K, ids: I, options: &XReadOptions, timeout: Duration)
+            -> Response<Vec<StreamKey>>
+        where K: ToRedisArgs,
+              I: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XREAD").arg(*options).arg("BLOCK").arg(millis(timeout))
+            .arg("STREAMS").arg(keys).arg(ids);
+
+        self.inner.query_with(cmd, StreamKey::from_read_reply)
+    }
+
+    /// Read entries from one or more streams as a member of a consumer
+    /// group, waiting for new entries if there are none.  See
+    /// `ClientHandle::xreadgroup`.
+    pub fn xreadgroup<G, C, K, I>(&mut self, group: G, consumer: C, keys: K, ids: I,
+                                  options: &XReadGroupOptions, timeout: Duration)
+            -> Response<Vec<StreamKey>>
+        where G: ToRedisArgs,
+              C: ToRedisArgs,
+              K: ToRedisArgs,
+              I: ToRedisArgs,
+    {
+        let cmd = xreadgroup_cmd(group, consumer, keys, ids, options, Some(timeout));
+
+        self.inner.query_with(cmd, StreamKey::from_read_reply)
+    }
+}
+
+/// Blocking commands take their timeout in (fractional) seconds.
+fn timeout_secs(timeout: Duration) -> f64 {
+    timeout.as_secs() as f64 + timeout.subsec_nanos() as f64 / 1_000_000_000.0
+}
@@ -0,0 +1,331 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+use futures::{Future, Stream};
+use futures::sync::mpsc;
+use tokio_core::net::TcpStream;
+use tokio_core::reactor::Handle;
+
+use {Cmd, ClientHandle};
+use transport::{self, RedisTransport};
+use types::{Value, from_redis_value};
+
+/// Configures client side caching, see `Client::cache`.
+#[derive(Clone, Debug)]
+pub struct CacheOptions {
+    capacity: usize,
+    mode: TrackingMode,
+}
+
+/// How the server decides which invalidation messages to send.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrackingMode {
+    /// The server remembers the keys read by the connection and only
+    /// sends invalidations for those.
+    Default,
+    /// The server sends invalidations for every key starting with one of
+    /// the prefixes, whether the connection read it or not.  Without any
+    /// prefix all keys are tracked.
+    Broadcast(Vec<String>),
+}
+
+/// Counters of the local cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of `get` calls answered from the local cache.
+    pub hits: u64,
+    /// Number of `get` calls that had to go to the server.
+    pub misses: u64,
+}
+
+/// The in-process LRU shared between a `ClientHandle` and the task
+/// processing invalidation messages.
+pub struct LocalCache {
+    capacity: usize,
+    // key -> (value, last use)
+    entries: HashMap<Vec<u8>, (Value, u64)>,
+    // last use -> key, the first entry is the least recently used one
+    lru: BTreeMap<u64, Vec<u8>>,
+    tick: u64,
+    // Keys fetched from the server whose reply has not arrived yet.  A
+    // key invalidated in the meantime is not stored once it does.
+    pending: HashSet<Vec<u8>>,
+    // Cleared once the connection is gone as invalidations can no
+    // longer be received.
+    enabled: bool,
+    stats: CacheStats,
+}
+
+impl CacheOptions {
+    /// Cache up to `capacity` keys using the default tracking mode.
+    pub fn new(capacity: usize) -> CacheOptions {
+        assert!(capacity > 0, "cache capacity must be at least one");
+
+        CacheOptions {
+            capacity: capacity,
+            mode: TrackingMode::Default,
+        }
+    }
+
+    /// Change the tracking mode.
+    pub fn mode(mut self, mode: TrackingMode) -> CacheOptions {
+        self.mode = mode;
+        self
+    }
+
+    fn tracking_cmd(&self) -> Cmd {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("TRACKING").arg("ON");
+
+        if let TrackingMode::Broadcast(ref prefixes) = self.mode {
+            cmd.arg("BCAST");
+            for prefix in prefixes.iter() {
+                cmd.arg("PREFIX").arg(&prefix[..]);
+            }
+        }
+
+        cmd
+    }
+}
+
+impl LocalCache {
+    fn new(capacity: usize) -> LocalCache {
+        LocalCache {
+            capacity: capacity,
+            entries: HashMap::new(),
+            lru: BTreeMap::new(),
+            tick: 0,
+            pending: HashSet::new(),
+            enabled: true,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Look up a key, counting the hit or miss.  On a miss the key is
+    /// marked as pending until `complete` is called with the reply.
+    pub fn lookup(&mut self, key: &[u8]) -> Option<Value> {
+        self.tick += 1;
+        let tick = self.tick;
+
+        let ret = match self.entries.get_mut(key) {
+            Some(&mut (ref value, ref mut used)) => {
+                self.lru.remove(used);
+                self.lru.insert(tick, key.to_vec());
+                *used = tick;
+                Some(value.clone())
+            }
+            None => None,
+        };
+
+        match ret {
+            Some(_) => self.stats.hits += 1,
+            None => {
+                self.stats.misses += 1;
+                self.pending.insert(key.to_vec());
+            }
+        }
+
+        ret
+    }
+
+    /// Store the reply to a lookup that missed, unless the key has been
+    /// invalidated since the request was sent.
+    pub fn complete(&mut self, key: Vec<u8>, value: Value) {
+        if !self.enabled || !self.pending.remove(&key) {
+            return;
+        }
+
+        if self.entries.len() >= self.capacity {
+            let oldest = self.lru.keys().next().cloned();
+            if let Some(oldest) = oldest {
+                let evicted = self.lru.remove(&oldest).unwrap();
+                self.entries.remove(&evicted);
+            }
+        }
+
+        self.tick += 1;
+        self.lru.insert(self.tick, key.clone());
+        self.entries.insert(key, (value, self.tick));
+    }
+
+    /// Forget about a lookup whose request failed.
+    pub fn abort(&mut self, key: &[u8]) {
+        self.pending.remove(key);
+    }
+
+    /// Handle a push message.  Everything besides `invalidate` messages
+    /// is ignored.  A nil key list means the server flushed its data.
+    fn on_push(&mut self, items: &[Value]) {
+        let kind: String = unwrap_or!(items.get(0).and_then(|v| from_redis_value(v).ok()),
+                                      return);
+        if kind != "invalidate" {
+            return;
+        }
+
+        match items.get(1) {
+            Some(&Value::Bulk(ref keys)) => {
+                for key in keys.iter() {
+                    if let Value::Data(ref key) = *key {
+                        self.invalidate(key);
+                    }
+                }
+            }
+            _ => self.clear(),
+        }
+    }
+
+    fn invalidate(&mut self, key: &[u8]) {
+        trace!("invalidating cached key; key={:?}", key);
+
+        self.pending.remove(key);
+        if let Some((_, used)) = self.entries.remove(key) {
+            self.lru.remove(&used);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.lru.clear();
+        self.pending.clear();
+    }
+}
+
+/// Connect a client with client side caching enabled.  The connection is
+/// switched to RESP3 so invalidation messages can be pushed on it, the
+/// naming commands are sent after that.
+pub fn connect(addr: &SocketAddr,
+               handle: &Handle,
+               options: CacheOptions,
+               setname: Vec<Cmd>,
+               setinfo: Vec<Cmd>)
+        -> Box<Future<Item = ClientHandle, Error = io::Error>>
+{
+    let (tx, rx) = mpsc::unbounded();
+    let cache = Rc::new(RefCell::new(LocalCache::new(options.capacity)));
+    let handle = handle.clone();
+
+    let mut hello = Cmd::new();
+    hello.arg("HELLO").arg(3);
+
+    let mut required = vec![hello, options.tracking_cmd()];
+    required.extend(setname);
+
+    let ret = TcpStream::connect(addr, &handle)
+        .and_then(move |io| {
+            transport::handshake(RedisTransport::with_push(io, tx), required, setinfo)
+        })
+        .map(move |transport| {
+            let client = ClientHandle::bind(transport, &handle, Some(cache.clone()));
+
+            let invalidations = rx.for_each({
+                let cache = cache.clone();
+                move |items| {
+                    cache.borrow_mut().on_push(&items);
+                    Ok(())
+                }
+            });
+
+            // The receiver ends with the connection, stop serving possibly
+            // stale values from then on.
+            handle.spawn(invalidations.then(move |_| {
+                let mut cache = cache.borrow_mut();
+                cache.enabled = false;
+                cache.clear();
+                Ok(())
+            }));
+
+            client
+        });
+
+    Box::new(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    fn fetch(cache: &mut LocalCache, key: &str) {
+        assert_eq!(cache.lookup(key.as_bytes()), None);
+        cache.complete(key.as_bytes().to_vec(), data(key));
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = LocalCache::new(2);
+        fetch(&mut cache, "a");
+        fetch(&mut cache, "b");
+
+        // Using "a" makes "b" the least recently used key.
+        assert_eq!(cache.lookup(b"a"), Some(data("a")));
+        fetch(&mut cache, "c");
+
+        assert_eq!(cache.lookup(b"b"), None);
+        assert_eq!(cache.lookup(b"a"), Some(data("a")));
+        assert_eq!(cache.lookup(b"c"), Some(data("c")));
+        assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 4 });
+    }
+
+    #[test]
+    fn invalidation_while_pending() {
+        let mut cache = LocalCache::new(2);
+        assert_eq!(cache.lookup(b"a"), None);
+
+        cache.on_push(&[data("invalidate"), Value::Bulk(vec![data("a")])]);
+        cache.complete(b"a".to_vec(), data("stale"));
+
+        assert_eq!(cache.lookup(b"a"), None);
+    }
+
+    #[test]
+    fn invalidation_evicts_key() {
+        let mut cache = LocalCache::new(2);
+        fetch(&mut cache, "a");
+        fetch(&mut cache, "b");
+
+        cache.on_push(&[data("invalidate"), Value::Bulk(vec![data("a")])]);
+
+        assert_eq!(cache.lookup(b"a"), None);
+        assert_eq!(cache.lookup(b"b"), Some(data("b")));
+    }
+
+    #[test]
+    fn nil_invalidation_clears() {
+        let mut cache = LocalCache::new(2);
+        fetch(&mut cache, "a");
+        fetch(&mut cache, "b");
+        assert_eq!(cache.lookup(b"c"), None);
+
+        cache.on_push(&[data("invalidate"), Value::Nil]);
+        cache.complete(b"c".to_vec(), data("c"));
+
+        assert_eq!(cache.lookup(b"a"), None);
+        assert_eq!(cache.lookup(b"b"), None);
+        assert_eq!(cache.lookup(b"c"), None);
+    }
+
+    #[test]
+    fn ignores_other_pushes() {
+        let mut cache = LocalCache::new(2);
+        fetch(&mut cache, "a");
+
+        cache.on_push(&[data("message"), data("a"), data("hello")]);
+        cache.on_push(&[]);
+
+        assert_eq!(cache.lookup(b"a"), Some(data("a")));
+    }
+}
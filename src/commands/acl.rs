@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+};
+use super::{field, field_map, strict_values, ClientInfo};
+
+/// The rules applied to a user by `ACL SETUSER`.  Rules are applied in
+/// the order they were added.
+#[derive(Clone, Debug, Default)]
+pub struct AclRules {
+    rules: Vec<String>,
+}
+
+impl AclRules {
+    pub fn new() -> AclRules {
+        AclRules::default()
+    }
+
+    /// Add a rule in the syntax of `ACL SETUSER`, for rules that have no
+    /// method of their own.
+    pub fn rule(mut self, rule: &str) -> AclRules {
+        self.rules.push(rule.to_string());
+        self
+    }
+
+    /// Reset the user to a disabled user without passwords and
+    /// permissions.
+    pub fn reset(self) -> AclRules {
+        self.rule("reset")
+    }
+
+    /// Enable the user.
+    pub fn on(self) -> AclRules {
+        self.rule("on")
+    }
+
+    /// Disable the user.  Existing connections stay authenticated.
+    pub fn off(self) -> AclRules {
+        self.rule("off")
+    }
+
+    /// Add a password.
+    pub fn password(self, password: &str) -> AclRules {
+        self.rule(&format!(">{}", password))
+    }
+
+    /// Add a password given as the hex encoded SHA-256 hash.
+    pub fn hashed_password(self, hash: &str) -> AclRules {
+        self.rule(&format!("#{}", hash))
+    }
+
+    /// Remove a password.
+    pub fn remove_password(self, password: &str) -> AclRules {
+        self.rule(&format!("<{}", password))
+    }
+
+    /// Remove all passwords and allow any password.
+    pub fn nopass(self) -> AclRules {
+        self.rule("nopass")
+    }
+
+    /// Remove all passwords without allowing any password.
+    pub fn reset_pass(self) -> AclRules {
+        self.rule("resetpass")
+    }
+
+    /// Allow access to the keys matching a glob style pattern.
+    pub fn keys(self, pattern: &str) -> AclRules {
+        self.rule(&format!("~{}", pattern))
+    }
+
+    /// Allow reading the keys matching a glob style pattern.
+    pub fn read_keys(self, pattern: &str) -> AclRules {
+        self.rule(&format!("%R~{}", pattern))
+    }
+
+    /// Allow writing the keys matching a glob style pattern.
+    pub fn write_keys(self, pattern: &str) -> AclRules {
+        self.rule(&format!("%W~{}", pattern))
+    }
+
+    /// Allow access to all keys.
+    pub fn all_keys(self) -> AclRules {
+        self.rule("allkeys")
+    }
+
+    /// Remove all key patterns.
+    pub fn reset_keys(self) -> AclRules {
+        self.rule("resetkeys")
+    }
+
+    /// Allow access to the pubsub channels matching a glob style pattern.
+    pub fn channels(self, pattern: &str) -> AclRules {
+        self.rule(&format!("&{}", pattern))
+    }
+
+    /// Allow access to all pubsub channels.
+    pub fn all_channels(self) -> AclRules {
+        self.rule("allchannels")
+    }
+
+    /// Remove all channel patterns.
+    pub fn reset_channels(self) -> AclRules {
+        self.rule("resetchannels")
+    }
+
+    /// Allow a command, or a subcommand given as `command|subcommand`.
+    pub fn allow_command(self, command: &str) -> AclRules {
+        self.rule(&format!("+{}", command))
+    }
+
+    /// Deny a command, or a subcommand given as `command|subcommand`.
+    pub fn deny_command(self, command: &str) -> AclRules {
+        self.rule(&format!("-{}", command))
+    }
+
+    /// Allow all commands of a category, such as `read` or `dangerous`.
+    pub fn allow_category(self, category: &str) -> AclRules {
+        self.rule(&format!("+@{}", category))
+    }
+
+    /// Deny all commands of a category.
+    pub fn deny_category(self, category: &str) -> AclRules {
+        self.rule(&format!("-@{}", category))
+    }
+
+    /// Allow all commands.
+    pub fn all_commands(self) -> AclRules {
+        self.rule("allcommands")
+    }
+
+    /// Deny all commands.
+    pub fn no_commands(self) -> AclRules {
+        self.rule("nocommands")
+    }
+}
+
+impl ToRedisArgs for AclRules {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        self.rules.to_redis_args()
+    }
+}
+
+/// A user as reported by `ACL GETUSER`.
+#[derive(Clone, Debug)]
+pub struct AclUser {
+    /// Flags such as `on`, `off` or `nopass`.
+    pub flags: Vec<String>,
+    /// The SHA-256 hashes of the passwords.
+    pub passwords: Vec<String>,
+    /// The command rules, for instance `+@all -debug`.
+    pub commands: String,
+    /// The key patterns, for instance `~cache:* %R~app:*`.  Servers older
+    /// than 7.0 report a list of patterns, which is converted to this form.
+    pub keys: String,
+    /// The channel patterns, for instance `&notifications:*`, converted
+    /// like `keys`.
+    pub channels: String,
+    /// Additional sets of permissions, any of which may grant access.
+    pub selectors: Vec<AclSelector>,
+}
+
+/// A selector of an `AclUser`.
+#[derive(Clone, Debug)]
+pub struct AclSelector {
+    pub commands: String,
+    pub keys: String,
+    pub channels: String,
+}
+
+impl AclUser {
+    /// Whether the user can authenticate.
+    pub fn is_enabled(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "on")
+    }
+}
+
+impl FromRedisValue for AclUser {
+    fn from_redis_value(v: &Value) -> RedisResult<AclUser> {
+        let map = try!(field_map(v));
+        Ok(AclUser {
+            flags: try!(field(&map, "flags")),
+            passwords: try!(field(&map, "passwords")),
+            commands: try!(field::<Option<String>>(&map, "commands")).unwrap_or_default(),
+            keys: try!(patterns(&map, "keys", "~")),
+            channels: try!(patterns(&map, "channels", "&")),
+            selectors: try!(field::<Option<_>>(&map, "selectors")).unwrap_or_default(),
+        })
+    }
+}
+
+impl FromRedisValue for AclSelector {
+    fn from_redis_value(v: &Value) -> RedisResult<AclSelector> {
+        let map = try!(field_map(v));
+        Ok(AclSelector {
+            commands: try!(field::<Option<String>>(&map, "commands")).unwrap_or_default(),
+            keys: try!(patterns(&map, "keys", "~")),
+            channels: try!(patterns(&map, "channels", "&")),
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<AclSelector>> {
+        strict_values(items)
+    }
+}
+
+/// Reads the key or channel patterns of a user.  They are a string of
+/// rules since 7.0 and a list of bare patterns before, which is turned
+/// into rules with the given prefix.
+fn patterns(map: &HashMap<String, Value>, name: &str, prefix: &str) -> RedisResult<String> {
+    match map.get(name) {
+        Some(&Value::Bulk(ref items)) => {
+            let patterns: Vec<String> = try!(strict_values(items));
+            let rules: Vec<String> = patterns.iter()
+                .map(|pattern| format!("{}{}", prefix, pattern))
+                .collect();
+            Ok(rules.join(" "))
+        }
+        _ => field::<Option<String>>(map, name).map(|v| v.unwrap_or_default()),
+    }
+}
+
+/// A denied command or authentication failure as reported by `ACL LOG`.
+/// Similar events are merged into a single entry.
+#[derive(Clone, Debug)]
+pub struct AclLogEntry {
+    /// The number of events merged into this entry.
+    pub count: u64,
+    /// Why access was denied: `command`, `key`, `channel` or `auth`.
+    pub reason: String,
+    /// Where the command was run, such as `toplevel`, `multi` or `lua`.
+    pub context: String,
+    /// The command, key or channel that was denied.
+    pub object: String,
+    pub username: String,
+    /// Time since the last event of this entry.
+    pub age: Duration,
+    /// The connection the last event came from.
+    pub client_info: ClientInfo,
+    /// The ID of the entry, not reported by servers older than 7.2.
+    pub entry_id: Option<u64>,
+    /// The unix time in milliseconds of the first event.
+    pub created: Option<u64>,
+    /// The unix time in milliseconds of the last event.
+    pub last_updated: Option<u64>,
+}
+
+impl FromRedisValue for AclLogEntry {
+    fn from_redis_value(v: &Value) -> RedisResult<AclLogEntry> {
+        let map = try!(field_map(v));
+        let age: f64 = try!(field(&map, "age-seconds"));
+        let client_info: String = try!(field(&map, "client-info"));
+        Ok(AclLogEntry {
+            count: try!(field(&map, "count")),
+            reason: try!(field(&map, "reason")),
+            context: try!(field(&map, "context")),
+            object: try!(field(&map, "object")),
+            username: try!(field(&map, "username")),
+            age: Duration::from_millis((age * 1000.0) as u64),
+            client_info: ClientInfo::parse(client_info.trim()),
+            entry_id: try!(field(&map, "entry-id")),
+            created: try!(field(&map, "timestamp-created")),
+            last_updated: try!(field(&map, "timestamp-last-updated")),
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<AclLogEntry>> {
+        strict_values(items)
+    }
+}
+
+impl ClientHandle {
+    /// Create a user or modify an existing one.
+    pub fn acl_setuser<U: ToRedisArgs>(&mut self, username: U, rules: &AclRules)
+            -> Response<()>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("SETUSER").arg(username).arg(rules.clone());
+
+        self.query(cmd)
+    }
+
+    /// Get the rules of a user, `None` if the user does not exist.
+    pub fn acl_getuser<U: ToRedisArgs>(&mut self, username: U) -> Response<Option<AclUser>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("GETUSER").arg(username);
+
+        self.query(cmd)
+    }
+
+    /// Delete users and close their connections.  Returns the number of
+    /// users that were deleted.
+    pub fn acl_deluser<U: ToRedisArgs>(&mut self, usernames: U) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("DELUSER").arg(usernames);
+
+        self.query(cmd)
+    }
+
+    /// List the users and their rules in the format of the ACL file.
+    pub fn acl_list(&mut self) -> Response<Vec<String>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("LIST");
+
+        self.query(cmd)
+    }
+
+    /// List the names of the users.
+    pub fn acl_users(&mut self) -> Response<Vec<String>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("USERS");
+
+        self.query(cmd)
+    }
+
+    /// Get the user this connection is authenticated as.
+    pub fn acl_whoami(&mut self) -> Response<String> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("WHOAMI");
+
+        self.query(cmd)
+    }
+
+    /// List the command categories, or the commands of a category.
+    pub fn acl_cat(&mut self, category: Option<&str>) -> Response<Vec<String>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("CAT").arg(category);
+
+        self.query(cmd)
+    }
+
+    /// Generate a random password, 256 bits long unless given otherwise.
+    pub fn acl_genpass(&mut self, bits: Option<usize>) -> Response<String> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("GENPASS").arg(bits);
+
+        self.query(cmd)
+    }
+
+    /// Get the most recent security events, optionally at most `count` of
+    /// them.
+    pub fn acl_log(&mut self, count: Option<usize>) -> Response<Vec<AclLogEntry>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("LOG").arg(count);
+
+        self.query(cmd)
+    }
+
+    /// Clear the security event log.
+    pub fn acl_log_reset(&mut self) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ACL").arg("LOG").arg("RESET");
+
+        self.query(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::from_redis_value;
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    fn user(keys: Value, channels: Value) -> Value {
+        Value::Bulk(vec![
+            data("flags"), Value::Bulk(vec![data("on")]),
+            data("passwords"), Value::Bulk(vec![]),
+            data("commands"), data("+@all"),
+            data("keys"), keys,
+            data("channels"), channels,
+        ])
+    }
+
+    #[test]
+    fn patterns_as_string() {
+        let user: AclUser = from_redis_value(&user(data("~cache:* %R~app:*"), data("&*")))
+            .unwrap();
+        assert!(user.is_enabled());
+        assert_eq!(user.keys, "~cache:* %R~app:*");
+        assert_eq!(user.channels, "&*");
+    }
+
+    #[test]
+    fn patterns_as_list() {
+        let keys = Value::Bulk(vec![data("cache:*"), data("app:*")]);
+        let channels = Value::Bulk(vec![data("*")]);
+        let user: AclUser = from_redis_value(&user(keys, channels)).unwrap();
+        assert_eq!(user.keys, "~cache:* ~app:*");
+        assert_eq!(user.channels, "&*");
+    }
+}
@@ -0,0 +1,261 @@
+use futures::Future;
+
+use {Cmd, ClientHandle, Response};
+use types::ToRedisArgs;
+
+/// A range of a string for `BITCOUNT` and `BITPOS`.  Negative offsets
+/// count from the end of the string.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct BitRange {
+    start: isize,
+    end: isize,
+    bits: bool,
+}
+
+impl BitRange {
+    /// A range given in bytes.  This is the default unit so no unit is
+    /// sent, which keeps the range working on servers older than 7.0.
+    pub fn bytes(start: isize, end: isize) -> BitRange {
+        BitRange { start: start, end: end, bits: false }
+    }
+
+    /// A range given in bits, requires Redis 7.0.
+    pub fn bits(start: isize, end: isize) -> BitRange {
+        BitRange { start: start, end: end, bits: true }
+    }
+}
+
+impl ToRedisArgs for BitRange {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = (self.start, self.end).to_redis_args();
+        if self.bits {
+            rv.push(b"BIT".to_vec());
+        }
+        rv
+    }
+}
+
+/// The operation performed by `BITOP`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BitOp {
+    And,
+    Or,
+    Xor,
+    /// Takes a single source key.
+    Not,
+}
+
+impl ToRedisArgs for BitOp {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            BitOp::And => "AND",
+            BitOp::Or => "OR",
+            BitOp::Xor => "XOR",
+            BitOp::Not => "NOT",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// The integer type of a `BITFIELD` operation.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BitEncoding {
+    /// A signed integer of up to 64 bits.
+    Signed(u8),
+    /// An unsigned integer of up to 63 bits.
+    Unsigned(u8),
+}
+
+impl ToRedisArgs for BitEncoding {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            BitEncoding::Signed(bits) => format!("i{}", bits),
+            BitEncoding::Unsigned(bits) => format!("u{}", bits),
+        };
+        vec![s.into_bytes()]
+    }
+}
+
+/// How `BITFIELD` handles the `set` and `incr_by` operations that follow
+/// when they overflow.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Overflow {
+    /// Wrap around.  This is the default.
+    Wrap,
+    /// Saturate at the minimum or maximum value.
+    Sat,
+    /// Do nothing and report nil.
+    Fail,
+}
+
+impl ToRedisArgs for Overflow {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            Overflow::Wrap => "WRAP",
+            Overflow::Sat => "SAT",
+            Overflow::Fail => "FAIL",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// The operations performed by a single `BITFIELD` call.  Offsets are in
+/// bits.
+#[derive(Clone, Debug, Default)]
+pub struct BitField {
+    args: Vec<Vec<u8>>,
+}
+
+impl BitField {
+    pub fn new() -> BitField {
+        BitField::default()
+    }
+
+    /// Get the value at `offset`.
+    pub fn get(mut self, encoding: BitEncoding, offset: usize) -> BitField {
+        self.args.extend(("GET", encoding, offset).to_redis_args());
+        self
+    }
+
+    /// Set the value at `offset`, reporting the old value.
+    pub fn set(mut self, encoding: BitEncoding, offset: usize, value: i64) -> BitField {
+        self.args.extend(("SET", encoding, offset, value).to_redis_args());
+        self
+    }
+
+    /// Increment the value at `offset`, reporting the new value.
+    pub fn incr_by(mut self, encoding: BitEncoding, offset: usize, delta: i64) -> BitField {
+        self.args.extend(("INCRBY", encoding, offset, delta).to_redis_args());
+        self
+    }
+
+    /// Change the overflow behavior of the operations added after this.
+    pub fn overflow(mut self, overflow: Overflow) -> BitField {
+        self.args.extend(("OVERFLOW", overflow).to_redis_args());
+        self
+    }
+}
+
+impl ToRedisArgs for BitField {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        self.args.clone()
+    }
+}
+
+/// The reads performed by a single `BITFIELD_RO` call.  Offsets are in
+/// bits.
+#[derive(Clone, Debug, Default)]
+pub struct BitFieldRo {
+    args: Vec<Vec<u8>>,
+}
+
+impl BitFieldRo {
+    pub fn new() -> BitFieldRo {
+        BitFieldRo::default()
+    }
+
+    /// Get the value at `offset`.
+    pub fn get(mut self, encoding: BitEncoding, offset: usize) -> BitFieldRo {
+        self.args.extend(("GET", encoding, offset).to_redis_args());
+        self
+    }
+}
+
+impl ToRedisArgs for BitFieldRo {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        self.args.clone()
+    }
+}
+
+impl ClientHandle {
+    /// Set or clear the bit at `offset`, returning the old bit.
+    pub fn setbit<K: ToRedisArgs>(&mut self, key: K, offset: usize, value: bool)
+            -> Response<bool>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SETBIT").arg(key).arg(offset).arg(if value { 1 } else { 0 });
+
+        self.query(cmd)
+    }
+
+    /// Get the bit at `offset`.
+    pub fn getbit<K: ToRedisArgs>(&mut self, key: K, offset: usize) -> Response<bool> {
+        let mut cmd = Cmd::new();
+        cmd.arg("GETBIT").arg(key).arg(offset);
+
+        self.query(cmd)
+    }
+
+    /// Count the set bits of a string, optionally only within `range`.
+    pub fn bitcount<K: ToRedisArgs>(&mut self, key: K, range: Option<BitRange>)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BITCOUNT").arg(key).arg(range);
+
+        self.query(cmd)
+    }
+
+    /// Find the position of the first set or clear bit, optionally only
+    /// within `range`.  Returns `None` if there is no such bit.
+    ///
+    /// Note that when looking for a clear bit without a range the string
+    /// is considered padded with zeros, so a position is always found.
+    pub fn bitpos<K: ToRedisArgs>(&mut self, key: K, bit: bool, range: Option<BitRange>)
+            -> Response<Option<usize>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BITPOS").arg(key).arg(if bit { 1 } else { 0 }).arg(range);
+
+        let ret = self.query::<i64>(cmd)
+            .map(|pos| if pos < 0 { None } else { Some(pos as usize) });
+
+        Box::new(ret)
+    }
+
+    /// Perform a bitwise operation between strings and store the result
+    /// in `destination`.  Returns the length of the stored string.
+    pub fn bitop<D: ToRedisArgs, K: ToRedisArgs>(&mut self, op: BitOp, destination: D, keys: K)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BITOP").arg(op).arg(destination).arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Perform the operations of `field` on a string, returning one value
+    /// per `get`, `set` and `incr_by`.  Operations that failed because of
+    /// `Overflow::Fail` report `None`.
+    pub fn bitfield<K: ToRedisArgs>(&mut self, key: K, field: &BitField)
+            -> Response<Vec<Option<i64>>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BITFIELD").arg(key).arg(field.clone());
+
+        self.query(cmd)
+    }
+
+    /// Like `bitfield` but only supports reads, which allows it to be sent
+    /// to read-only replicas.  Returns one value per `get`.
+    pub fn bitfield_ro<K: ToRedisArgs>(&mut self, key: K, field: &BitFieldRo)
+            -> Response<Vec<i64>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("BITFIELD_RO").arg(key).arg(field.clone());
+
+        self.query(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_units() {
+        let args = |v: &[&str]| v.iter().map(|s| s.as_bytes().to_vec()).collect::<Vec<_>>();
+        assert_eq!(BitRange::bytes(0, -1).to_redis_args(), args(&["0", "-1"]));
+        assert_eq!(BitRange::bits(3, 9).to_redis_args(), args(&["3", "9", "BIT"]));
+    }
+}
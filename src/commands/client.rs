@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+    from_redis_value,
+};
+use super::millis;
+
+/// The kind of a client connection.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ClientType {
+    Normal,
+    Master,
+    Replica,
+    PubSub,
+}
+
+impl ToRedisArgs for ClientType {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            ClientType::Normal => "NORMAL",
+            ClientType::Master => "MASTER",
+            ClientType::Replica => "REPLICA",
+            ClientType::PubSub => "PUBSUB",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// Which commands are held back by `CLIENT PAUSE`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PauseMode {
+    /// Pause all commands.
+    All,
+    /// Only pause commands that may write.
+    Write,
+}
+
+impl ToRedisArgs for PauseMode {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            PauseMode::All => "ALL",
+            PauseMode::Write => "WRITE",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// A connection as reported by `CLIENT LIST` and `CLIENT INFO`.
+///
+/// The commonly used properties are parsed into fields, all of them are
+/// available as strings through `get`.
+#[derive(Clone, Debug)]
+pub struct ClientInfo {
+    pub id: u64,
+    /// The address of the client.
+    pub addr: String,
+    /// The address of the server the client connected to.
+    pub laddr: String,
+    /// The name set with `CLIENT SETNAME`, empty if there is none.
+    pub name: String,
+    /// Time since the connection was established.
+    pub age: Duration,
+    /// Time since the last command.
+    pub idle: Duration,
+    /// The client flags, such as `N` for a normal client.
+    pub flags: String,
+    /// The selected database.
+    pub db: u32,
+    /// The last command run by the client.
+    pub cmd: String,
+    /// The authenticated ACL user.
+    pub user: String,
+    /// The library name set with `CLIENT SETINFO`, empty if there is none.
+    pub lib_name: String,
+    /// The library version set with `CLIENT SETINFO`, empty if there is
+    /// none.
+    pub lib_ver: String,
+    properties: HashMap<String, String>,
+}
+
+impl ClientInfo {
+    /// Parses a single line of `CLIENT LIST`, made of space separated
+    /// `name=value` properties.
+    pub fn parse(line: &str) -> ClientInfo {
+        let mut properties = HashMap::new();
+        for prop in line.split(' ') {
+            let mut p = prop.splitn(2, '=');
+            let k = unwrap_or!(p.next(), continue);
+            let v = unwrap_or!(p.next(), continue);
+            properties.insert(k.to_string(), v.to_string());
+        }
+
+        let string = |name: &str| properties.get(name).cloned().unwrap_or_default();
+        let number = |name: &str| {
+            properties.get(name).and_then(|v| v.parse().ok()).unwrap_or(0)
+        };
+
+        ClientInfo {
+            id: number("id"),
+            addr: string("addr"),
+            laddr: string("laddr"),
+            name: string("name"),
+            age: Duration::from_secs(number("age")),
+            idle: Duration::from_secs(number("idle")),
+            flags: string("flags"),
+            db: number("db") as u32,
+            cmd: string("cmd"),
+            user: string("user"),
+            lib_name: string("lib-name"),
+            lib_ver: string("lib-ver"),
+            properties: properties,
+        }
+    }
+
+    /// Gets any property by name, for instance `qbuf` or `tot-mem`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(|v| &v[..])
+    }
+}
+
+impl FromRedisValue for ClientInfo {
+    fn from_redis_value(v: &Value) -> RedisResult<ClientInfo> {
+        let s: String = try!(from_redis_value(v));
+        Ok(ClientInfo::parse(s.trim()))
+    }
+}
+
+/// Selects the connections closed by `CLIENT KILL`.  All given filters
+/// have to match.
+#[derive(Clone, Debug, Default)]
+pub struct ClientKillFilter {
+    args: Vec<Vec<u8>>,
+}
+
+impl ClientKillFilter {
+    pub fn new() -> ClientKillFilter {
+        ClientKillFilter::default()
+    }
+
+    /// The connection with the given ID.
+    pub fn id(mut self, id: u64) -> ClientKillFilter {
+        self.args.extend(("ID", id).to_redis_args());
+        self
+    }
+
+    /// The connection from the given `ip:port`.
+    pub fn addr(mut self, addr: &str) -> ClientKillFilter {
+        self.args.extend(("ADDR", addr).to_redis_args());
+        self
+    }
+
+    /// The connections made to the given local `ip:port` of the server.
+    pub fn laddr(mut self, laddr: &str) -> ClientKillFilter {
+        self.args.extend(("LADDR", laddr).to_redis_args());
+        self
+    }
+
+    /// The connections of the given kind.
+    pub fn client_type(mut self, client_type: ClientType) -> ClientKillFilter {
+        self.args.extend(("TYPE", client_type).to_redis_args());
+        self
+    }
+
+    /// The connections authenticated as the given ACL user.
+    pub fn user(mut self, user: &str) -> ClientKillFilter {
+        self.args.extend(("USER", user).to_redis_args());
+        self
+    }
+
+    /// Whether the calling connection may be closed as well.  It is
+    /// skipped by default.
+    pub fn skipme(mut self, skip: bool) -> ClientKillFilter {
+        self.args.extend(("SKIPME", if skip { "yes" } else { "no" }).to_redis_args());
+        self
+    }
+
+    /// The connections older than `age`.
+    pub fn maxage(mut self, age: Duration) -> ClientKillFilter {
+        self.args.extend(("MAXAGE", age.as_secs()).to_redis_args());
+        self
+    }
+}
+
+impl ToRedisArgs for ClientKillFilter {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        self.args.clone()
+    }
+}
+
+impl ClientHandle {
+    /// Get the ID of this connection.
+    pub fn client_id(&mut self) -> Response<u64> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("ID");
+
+        self.query(cmd)
+    }
+
+    /// Set the name of this connection, shown by `CLIENT LIST`.
+    pub fn client_setname<N: ToRedisArgs>(&mut self, name: N) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("SETNAME").arg(name);
+
+        self.query(cmd)
+    }
+
+    /// Get the name of this connection.
+    pub fn client_getname(&mut self) -> Response<Option<String>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("GETNAME");
+
+        self.query(cmd)
+    }
+
+    /// List the connections to the server, optionally only those of the
+    /// given kind.
+    pub fn client_list(&mut self, client_type: Option<ClientType>) -> Response<Vec<ClientInfo>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("LIST");
+        if let Some(client_type) = client_type {
+            cmd.arg("TYPE").arg(client_type);
+        }
+
+        self.query_with(cmd, |v| {
+            let s: String = try!(from_redis_value(v));
+            Ok(s.lines().filter(|l| !l.is_empty()).map(ClientInfo::parse).collect())
+        })
+    }
+
+    /// Get the properties of this connection.
+    pub fn client_info(&mut self) -> Response<ClientInfo> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("INFO");
+
+        self.query(cmd)
+    }
+
+    /// Close the connections matching `filter`, returning their number.
+    pub fn client_kill(&mut self, filter: &ClientKillFilter) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("KILL").arg(filter.clone());
+
+        self.query(cmd)
+    }
+
+    /// Suspend the commands of all normal and pubsub clients for
+    /// `timeout`.
+    pub fn client_pause(&mut self, timeout: Duration, mode: PauseMode) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("PAUSE").arg(millis(timeout)).arg(mode);
+
+        self.query(cmd)
+    }
+
+    /// Resume the clients suspended by `client_pause`.
+    pub fn client_unpause(&mut self) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("UNPAUSE");
+
+        self.query(cmd)
+    }
+
+    /// Protect this connection from being closed by the `maxmemory-clients`
+    /// limit.
+    pub fn client_no_evict(&mut self, enabled: bool) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("NO-EVICT").arg(if enabled { "ON" } else { "OFF" });
+
+        self.query(cmd)
+    }
+
+    /// Stop the commands of this connection from updating the access time
+    /// of keys, used by the LRU and LFU eviction policies.
+    pub fn client_no_touch(&mut self, enabled: bool) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CLIENT").arg("NO-TOUCH").arg(if enabled { "ON" } else { "OFF" });
+
+        self.query(cmd)
+    }
+}
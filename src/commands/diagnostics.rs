@@ -0,0 +1,285 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+    from_redis_value,
+};
+use super::{invalid_reply, strict_values};
+
+/// A command recorded by the slow log.
+#[derive(Clone, Debug)]
+pub struct SlowlogEntry {
+    /// The ID of the entry, unique until the server restarts.
+    pub id: u64,
+    /// When the command was run.
+    pub timestamp: SystemTime,
+    /// How long the command took to execute.
+    pub duration: Duration,
+    /// The command and its arguments, possibly shortened by the server.
+    /// Arguments are binary safe, so they are kept as bytes.
+    pub args: Vec<Vec<u8>>,
+    /// The address of the client, not reported by servers older than 4.0.
+    pub client_addr: Option<String>,
+    /// The name of the client, not reported by servers older than 4.0.
+    pub client_name: Option<String>,
+}
+
+impl FromRedisValue for SlowlogEntry {
+    fn from_redis_value(v: &Value) -> RedisResult<SlowlogEntry> {
+        let items = match *v {
+            Value::Bulk(ref items) if items.len() >= 4 => items,
+            _ => return invalid_reply(v, "Not a slow log entry"),
+        };
+
+        let timestamp: u64 = try!(from_redis_value(&items[1]));
+        let micros: u64 = try!(from_redis_value(&items[2]));
+        let optional = |idx: usize| -> RedisResult<Option<String>> {
+            match items.get(idx) {
+                Some(item) => from_redis_value(item),
+                None => Ok(None),
+            }
+        };
+
+        Ok(SlowlogEntry {
+            id: try!(from_redis_value(&items[0])),
+            timestamp: UNIX_EPOCH + Duration::from_secs(timestamp),
+            duration: Duration::from_micros(micros),
+            args: match items[3] {
+                Value::Bulk(ref args) => try!(strict_values(args)),
+                _ => return invalid_reply(v, "Not a slow log entry"),
+            },
+            client_addr: try!(optional(4)),
+            client_name: try!(optional(5)).and_then(|name| {
+                if name.is_empty() { None } else { Some(name) }
+            }),
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<SlowlogEntry>> {
+        strict_values(items)
+    }
+}
+
+/// The latest spike of a latency event as reported by `LATENCY LATEST`.
+#[derive(Clone, Debug)]
+pub struct LatencyLatest {
+    /// The name of the event, such as `command` or `fork`.
+    pub event: String,
+    /// When the latest spike happened.
+    pub timestamp: SystemTime,
+    /// The latency of the latest spike.
+    pub latest: Duration,
+    /// The highest latency recorded for the event.
+    pub max: Duration,
+}
+
+impl FromRedisValue for LatencyLatest {
+    fn from_redis_value(v: &Value) -> RedisResult<LatencyLatest> {
+        let items = match *v {
+            Value::Bulk(ref items) if items.len() >= 4 => items,
+            _ => return invalid_reply(v, "Not a latency event"),
+        };
+
+        let timestamp: u64 = try!(from_redis_value(&items[1]));
+        let latest: u64 = try!(from_redis_value(&items[2]));
+        let max: u64 = try!(from_redis_value(&items[3]));
+
+        Ok(LatencyLatest {
+            event: try!(from_redis_value(&items[0])),
+            timestamp: UNIX_EPOCH + Duration::from_secs(timestamp),
+            latest: Duration::from_millis(latest),
+            max: Duration::from_millis(max),
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<LatencyLatest>> {
+        strict_values(items)
+    }
+}
+
+/// A latency spike as reported by `LATENCY HISTORY`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencySample {
+    pub timestamp: SystemTime,
+    pub latency: Duration,
+}
+
+impl FromRedisValue for LatencySample {
+    fn from_redis_value(v: &Value) -> RedisResult<LatencySample> {
+        let (timestamp, latency): (u64, u64) = try!(from_redis_value(v));
+        Ok(LatencySample {
+            timestamp: UNIX_EPOCH + Duration::from_secs(timestamp),
+            latency: Duration::from_millis(latency),
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<LatencySample>> {
+        strict_values(items)
+    }
+}
+
+impl ClientHandle {
+    /// Get the most recent slow log entries, optionally at most `count` of
+    /// them.  The server returns 10 entries by default.
+    pub fn slowlog_get(&mut self, count: Option<isize>) -> Response<Vec<SlowlogEntry>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SLOWLOG").arg("GET").arg(count);
+
+        self.query(cmd)
+    }
+
+    /// Get the number of entries in the slow log.
+    pub fn slowlog_len(&mut self) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SLOWLOG").arg("LEN");
+
+        self.query(cmd)
+    }
+
+    /// Clear the slow log.
+    pub fn slowlog_reset(&mut self) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SLOWLOG").arg("RESET");
+
+        self.query(cmd)
+    }
+
+    /// Get the latest latency spike of every event.  Events are only
+    /// recorded if `latency-monitor-threshold` is set.
+    pub fn latency_latest(&mut self) -> Response<Vec<LatencyLatest>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("LATENCY").arg("LATEST");
+
+        self.query(cmd)
+    }
+
+    /// Get the recorded latency spikes of an event.
+    pub fn latency_history<E: ToRedisArgs>(&mut self, event: E) -> Response<Vec<LatencySample>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("LATENCY").arg("HISTORY").arg(event);
+
+        self.query(cmd)
+    }
+
+    /// Clear the recorded spikes of the given events, or of all events if
+    /// none are given.  Returns the number of events that were reset.
+    pub fn latency_reset<E: ToRedisArgs>(&mut self, events: E) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("LATENCY").arg("RESET").arg(events);
+
+        self.query(cmd)
+    }
+
+    /// Get a human readable analysis of the recorded latency spikes.
+    pub fn latency_doctor(&mut self) -> Response<String> {
+        let mut cmd = Cmd::new();
+        cmd.arg("LATENCY").arg("DOCTOR");
+
+        self.query(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    fn slowlog(args: Vec<Value>, client: &[&str]) -> Value {
+        let mut items = vec![Value::Int(7), Value::Int(1700000000), Value::Int(1500),
+                             Value::Bulk(args)];
+        items.extend(client.iter().map(|c| data(c)));
+        Value::Bulk(items)
+    }
+
+    #[test]
+    fn slowlog_entry() {
+        let reply = slowlog(vec![data("GET"), data("foo")], &["127.0.0.1:5000", "worker"]);
+        let entry: SlowlogEntry = from_redis_value(&reply).unwrap();
+        assert_eq!(entry.id, 7);
+        assert_eq!(entry.timestamp, UNIX_EPOCH + Duration::from_secs(1700000000));
+        assert_eq!(entry.duration, Duration::from_micros(1500));
+        assert_eq!(entry.args, vec![b"GET".to_vec(), b"foo".to_vec()]);
+        assert_eq!(entry.client_addr, Some("127.0.0.1:5000".to_string()));
+        assert_eq!(entry.client_name, Some("worker".to_string()));
+    }
+
+    #[test]
+    fn slowlog_entry_legacy() {
+        // Servers older than 4.0 do not report the client
+        let reply = slowlog(vec![data("KEYS"), data("*")], &[]);
+        let entry: SlowlogEntry = from_redis_value(&reply).unwrap();
+        assert_eq!(entry.args.len(), 2);
+        assert_eq!(entry.client_addr, None);
+        assert_eq!(entry.client_name, None);
+    }
+
+    #[test]
+    fn slowlog_entry_unnamed_client() {
+        let reply = slowlog(vec![data("PING")], &["127.0.0.1:5000", ""]);
+        let entry: SlowlogEntry = from_redis_value(&reply).unwrap();
+        assert_eq!(entry.client_addr, Some("127.0.0.1:5000".to_string()));
+        assert_eq!(entry.client_name, None);
+    }
+
+    #[test]
+    fn slowlog_entry_binary_args() {
+        let reply = slowlog(vec![data("SET"), Value::Data(vec![0xff, 0x00, 0xfe])], &[]);
+        let entry: SlowlogEntry = from_redis_value(&reply).unwrap();
+        assert_eq!(entry.args[1], vec![0xff, 0x00, 0xfe]);
+    }
+
+    #[test]
+    fn slowlog_entry_malformed() {
+        let reply = Value::Bulk(vec![
+            slowlog(vec![data("GET"), data("foo")], &[]),
+            Value::Bulk(vec![Value::Int(8), Value::Int(1700000000), Value::Int(10)]),
+        ]);
+        assert!(from_redis_value::<Vec<SlowlogEntry>>(&reply).is_err());
+
+        let reply = Value::Bulk(vec![Value::Int(8), Value::Int(1700000000), Value::Int(10),
+                                     data("GET foo")]);
+        assert!(from_redis_value::<SlowlogEntry>(&reply).is_err());
+    }
+
+    #[test]
+    fn latency_latest() {
+        let reply = Value::Bulk(vec![
+            Value::Bulk(vec![data("command"), Value::Int(1700000000), Value::Int(250),
+                             Value::Int(1000)]),
+        ]);
+        let events: Vec<LatencyLatest> = from_redis_value(&reply).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "command");
+        assert_eq!(events[0].latest, Duration::from_millis(250));
+        assert_eq!(events[0].max, Duration::from_secs(1));
+
+        let reply = Value::Bulk(vec![Value::Bulk(vec![data("fork"), Value::Int(1700000000)])]);
+        assert!(from_redis_value::<Vec<LatencyLatest>>(&reply).is_err());
+    }
+
+    #[test]
+    fn latency_history() {
+        let reply = Value::Bulk(vec![
+            Value::Bulk(vec![Value::Int(1700000000), Value::Int(250)]),
+            Value::Bulk(vec![Value::Int(1700000010), Value::Int(300)]),
+        ]);
+        let samples: Vec<LatencySample> = from_redis_value(&reply).unwrap();
+        assert_eq!(samples[1], LatencySample {
+            timestamp: UNIX_EPOCH + Duration::from_secs(1700000010),
+            latency: Duration::from_millis(300),
+        });
+
+        let reply = Value::Bulk(vec![
+            Value::Bulk(vec![Value::Int(1700000000), Value::Int(250)]),
+            Value::Bulk(vec![data("soon"), Value::Int(300)]),
+        ]);
+        assert!(from_redis_value::<Vec<LatencySample>>(&reply).is_err());
+    }
+}
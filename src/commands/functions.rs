@@ -0,0 +1,183 @@
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+};
+use super::{counted_args, field, field_map, strict_values};
+
+/// What `FUNCTION RESTORE` should do with the libraries that already
+/// exist on the server.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RestorePolicy {
+    /// Delete all existing libraries before restoring.
+    Flush,
+    /// Append the restored libraries, failing on name collisions.
+    Append,
+    /// Replace existing libraries that have the same name.
+    Replace,
+}
+
+impl ToRedisArgs for RestorePolicy {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            RestorePolicy::Flush => "FLUSH",
+            RestorePolicy::Append => "APPEND",
+            RestorePolicy::Replace => "REPLACE",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// A library as reported by `FUNCTION LIST`.
+#[derive(Clone, Debug)]
+pub struct FunctionLibrary {
+    /// The name of the library.
+    pub name: String,
+    /// The engine the library runs on (`LUA` for now).
+    pub engine: String,
+    /// The functions registered by the library.
+    pub functions: Vec<FunctionInfo>,
+    /// The source of the library.  Only set if the listing was
+    /// requested with code.
+    pub code: Option<String>,
+}
+
+/// A single function inside of a `FunctionLibrary`.
+#[derive(Clone, Debug)]
+pub struct FunctionInfo {
+    /// The name the function is called with from `FCALL`.
+    pub name: String,
+    /// The description the function was registered with.
+    pub description: Option<String>,
+    /// Flags such as `no-writes` or `allow-stale`.
+    pub flags: Vec<String>,
+}
+
+impl FromRedisValue for FunctionLibrary {
+    fn from_redis_value(v: &Value) -> RedisResult<FunctionLibrary> {
+        let map = try!(field_map(v));
+        Ok(FunctionLibrary {
+            name: try!(field(&map, "library_name")),
+            engine: try!(field(&map, "engine")),
+            functions: try!(field(&map, "functions")),
+            code: try!(field(&map, "library_code")),
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<FunctionLibrary>> {
+        strict_values(items)
+    }
+}
+
+impl FromRedisValue for FunctionInfo {
+    fn from_redis_value(v: &Value) -> RedisResult<FunctionInfo> {
+        let map = try!(field_map(v));
+        Ok(FunctionInfo {
+            name: try!(field(&map, "name")),
+            description: try!(field(&map, "description")),
+            flags: try!(field(&map, "flags")),
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<FunctionInfo>> {
+        strict_values(items)
+    }
+}
+
+impl ClientHandle {
+    /// Load a library of functions and return the library name.  With
+    /// `replace` set an existing library of the same name is replaced,
+    /// otherwise loading it fails.
+    pub fn function_load<C: ToRedisArgs>(&mut self, code: C, replace: bool) -> Response<String> {
+        let mut cmd = Cmd::new();
+        cmd.arg("FUNCTION").arg("LOAD");
+        if replace {
+            cmd.arg("REPLACE");
+        }
+        cmd.arg(code);
+
+        self.query(cmd)
+    }
+
+    /// List the loaded libraries, optionally only the ones matching
+    /// `pattern`.  The library code is only included if `with_code`
+    /// is set.
+    pub fn function_list(&mut self, pattern: Option<&str>, with_code: bool)
+            -> Response<Vec<FunctionLibrary>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("FUNCTION").arg("LIST");
+        if let Some(pattern) = pattern {
+            cmd.arg("LIBRARYNAME").arg(pattern);
+        }
+        if with_code {
+            cmd.arg("WITHCODE");
+        }
+
+        self.query(cmd)
+    }
+
+    /// Delete a library and all of its functions.
+    pub fn function_delete<N: ToRedisArgs>(&mut self, library: N) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("FUNCTION").arg("DELETE").arg(library);
+
+        self.query(cmd)
+    }
+
+    /// Return a serialized payload of all loaded libraries that can be
+    /// passed to `function_restore`.
+    pub fn function_dump(&mut self) -> Response<Vec<u8>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("FUNCTION").arg("DUMP");
+
+        self.query(cmd)
+    }
+
+    /// Restore libraries from a payload created by `function_dump`.  If
+    /// no policy is given the server defaults to `APPEND`.
+    pub fn function_restore(&mut self, payload: &[u8], policy: Option<RestorePolicy>)
+            -> Response<()>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("FUNCTION").arg("RESTORE").arg(payload).arg(policy);
+
+        self.query(cmd)
+    }
+
+    /// Invoke a function.  The number of keys is derived from `keys` so
+    /// any argument type producing multiple arguments can be passed.
+    pub fn fcall<F, K, A, T>(&mut self, function: F, keys: K, args: A) -> Response<T>
+        where F: ToRedisArgs,
+              K: ToRedisArgs,
+              A: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        self.query(fcall_cmd("FCALL", function, keys, args))
+    }
+
+    /// Like `fcall` but for functions flagged `no-writes`.  This can be
+    /// used against read-only replicas.
+    pub fn fcall_ro<F, K, A, T>(&mut self, function: F, keys: K, args: A) -> Response<T>
+        where F: ToRedisArgs,
+              K: ToRedisArgs,
+              A: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        self.query(fcall_cmd("FCALL_RO", function, keys, args))
+    }
+}
+
+fn fcall_cmd<F, K, A>(name: &str, function: F, keys: K, args: A) -> Cmd
+    where F: ToRedisArgs,
+          K: ToRedisArgs,
+          A: ToRedisArgs,
+{
+    let mut cmd = Cmd::new();
+    cmd.arg(name).arg(function);
+    counted_args(&mut cmd, keys);
+    cmd.arg(args);
+    cmd
+}
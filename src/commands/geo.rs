@@ -0,0 +1,393 @@
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+    from_redis_value,
+};
+use super::{invalid_reply, strict_values};
+
+/// The unit of distances passed to and returned by the geo commands.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum GeoUnit {
+    Meters,
+    Kilometers,
+    Miles,
+    Feet,
+}
+
+impl ToRedisArgs for GeoUnit {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            GeoUnit::Meters => "m",
+            GeoUnit::Kilometers => "km",
+            GeoUnit::Miles => "mi",
+            GeoUnit::Feet => "ft",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// Options for `ClientHandle::geoadd`.
+#[derive(Clone, Debug, Default)]
+pub struct GeoAddOptions {
+    condition: Option<&'static str>,
+    ch: bool,
+}
+
+impl GeoAddOptions {
+    pub fn new() -> GeoAddOptions {
+        GeoAddOptions::default()
+    }
+
+    /// Only add new members, never update existing ones.
+    pub fn nx(mut self) -> GeoAddOptions {
+        self.condition = Some("NX");
+        self
+    }
+
+    /// Only update existing members, never add new ones.
+    pub fn xx(mut self) -> GeoAddOptions {
+        self.condition = Some("XX");
+        self
+    }
+
+    /// Return the number of changed members instead of the number of
+    /// added ones.
+    pub fn ch(mut self) -> GeoAddOptions {
+        self.ch = true;
+        self
+    }
+}
+
+impl ToRedisArgs for GeoAddOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if let Some(condition) = self.condition {
+            rv.extend(condition.to_redis_args());
+        }
+        if self.ch {
+            rv.extend("CH".to_redis_args());
+        }
+        rv
+    }
+}
+
+/// The area searched by `GEOSEARCH`.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum GeoShape {
+    /// A circle with the given radius.
+    Radius(f64, GeoUnit),
+    /// A box with the given width and height.
+    Box(f64, f64, GeoUnit),
+}
+
+impl ToRedisArgs for GeoShape {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        match *self {
+            GeoShape::Radius(radius, unit) => ("BYRADIUS", radius, unit).to_redis_args(),
+            GeoShape::Box(width, height, unit) => {
+                ("BYBOX", width, height, unit).to_redis_args()
+            }
+        }
+    }
+}
+
+/// A query for `GEOSEARCH` and `GEOSEARCHSTORE`.
+#[derive(Clone, Debug)]
+pub struct GeoSearch {
+    origin: Vec<Vec<u8>>,
+    shape: GeoShape,
+    order: Option<&'static str>,
+    count: Option<(usize, bool)>,
+    with_coord: bool,
+    with_dist: bool,
+    with_hash: bool,
+}
+
+impl GeoSearch {
+    /// Search around the position of an existing member.
+    pub fn from_member<M: ToRedisArgs>(member: M, shape: GeoShape) -> GeoSearch {
+        let mut origin = "FROMMEMBER".to_redis_args();
+        origin.extend(member.to_redis_args());
+        GeoSearch::new(origin, shape)
+    }
+
+    /// Search around the given longitude and latitude.
+    pub fn from_lonlat(longitude: f64, latitude: f64, shape: GeoShape) -> GeoSearch {
+        GeoSearch::new(("FROMLONLAT", longitude, latitude).to_redis_args(), shape)
+    }
+
+    fn new(origin: Vec<Vec<u8>>, shape: GeoShape) -> GeoSearch {
+        GeoSearch {
+            origin: origin,
+            shape: shape,
+            order: None,
+            count: None,
+            with_coord: false,
+            with_dist: false,
+            with_hash: false,
+        }
+    }
+
+    /// Sort the results from the nearest to the farthest.
+    pub fn asc(mut self) -> GeoSearch {
+        self.order = Some("ASC");
+        self
+    }
+
+    /// Sort the results from the farthest to the nearest.
+    pub fn desc(mut self) -> GeoSearch {
+        self.order = Some("DESC");
+        self
+    }
+
+    /// Return at most `count` results.  With `any` the search stops as
+    /// soon as enough matches were found, so they are not necessarily the
+    /// nearest ones.
+    pub fn count(mut self, count: usize, any: bool) -> GeoSearch {
+        self.count = Some((count, any));
+        self
+    }
+
+    /// Include the position of the results.
+    pub fn with_coord(mut self) -> GeoSearch {
+        self.with_coord = true;
+        self
+    }
+
+    /// Include the distance of the results from the origin, in the unit
+    /// of the shape.
+    pub fn with_dist(mut self) -> GeoSearch {
+        self.with_dist = true;
+        self
+    }
+
+    /// Include the raw geohash of the results.
+    pub fn with_hash(mut self) -> GeoSearch {
+        self.with_hash = true;
+        self
+    }
+
+    /// The arguments shared by `GEOSEARCH` and `GEOSEARCHSTORE`.
+    fn search_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = self.origin.clone();
+        rv.extend(self.shape.to_redis_args());
+        if let Some(order) = self.order {
+            rv.extend(order.to_redis_args());
+        }
+        if let Some((count, any)) = self.count {
+            rv.extend(("COUNT", count).to_redis_args());
+            if any {
+                rv.extend("ANY".to_redis_args());
+            }
+        }
+        rv
+    }
+}
+
+impl ToRedisArgs for GeoSearch {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = self.search_args();
+        if self.with_coord {
+            rv.extend("WITHCOORD".to_redis_args());
+        }
+        if self.with_dist {
+            rv.extend("WITHDIST".to_redis_args());
+        }
+        if self.with_hash {
+            rv.extend("WITHHASH".to_redis_args());
+        }
+        rv
+    }
+}
+
+/// A member found by `GEOSEARCH`.  The optional fields are only set if
+/// they were requested.
+#[derive(PartialEq, Clone, Debug)]
+pub struct GeoResult {
+    pub member: String,
+    /// The distance from the origin of the search.
+    pub dist: Option<f64>,
+    /// The raw geohash as a 52 bit integer.
+    pub hash: Option<u64>,
+    /// The longitude and latitude.
+    pub coord: Option<(f64, f64)>,
+}
+
+impl FromRedisValue for GeoResult {
+    fn from_redis_value(v: &Value) -> RedisResult<GeoResult> {
+        let items = match *v {
+            // Without any of the `with_*` flags only the member is sent
+            Value::Data(_) => return Ok(GeoResult {
+                member: try!(from_redis_value(v)),
+                dist: None,
+                hash: None,
+                coord: None,
+            }),
+            Value::Bulk(ref items) if !items.is_empty() => items,
+            _ => return invalid_reply(v, "Not a geo search result"),
+        };
+
+        // The extra fields come in a fixed order but only the requested
+        // ones are present, each of them has a distinct type.
+        let mut rv = GeoResult {
+            member: try!(from_redis_value(&items[0])),
+            dist: None,
+            hash: None,
+            coord: None,
+        };
+        for item in items[1..].iter() {
+            match *item {
+                Value::Data(_) => rv.dist = Some(try!(from_redis_value(item))),
+                Value::Int(_) => rv.hash = Some(try!(from_redis_value(item))),
+                Value::Bulk(_) => rv.coord = Some(try!(from_redis_value(item))),
+                _ => return invalid_reply(v, "Not a geo search result"),
+            }
+        }
+        Ok(rv)
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<GeoResult>> {
+        strict_values(items)
+    }
+}
+
+impl ClientHandle {
+    /// Add members with their positions, given as `(longitude, latitude,
+    /// member)`.  Returns the number of added members, or with `ch` the
+    /// number of changed ones.
+    pub fn geoadd<K, M>(&mut self, key: K, items: &[(f64, f64, M)], options: &GeoAddOptions)
+            -> Response<usize>
+        where K: ToRedisArgs,
+              M: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("GEOADD").arg(key).arg(options.clone()).arg(items);
+
+        self.query(cmd)
+    }
+
+    /// Get the longitude and latitude of members, `None` for members that
+    /// do not exist.
+    pub fn geopos<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, members: M)
+            -> Response<Vec<Option<(f64, f64)>>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("GEOPOS").arg(key).arg(members);
+
+        self.query(cmd)
+    }
+
+    /// Get the distance between two members, `None` if either of them
+    /// does not exist.
+    pub fn geodist<K, M1, M2>(&mut self, key: K, member1: M1, member2: M2, unit: GeoUnit)
+            -> Response<Option<f64>>
+        where K: ToRedisArgs,
+              M1: ToRedisArgs,
+              M2: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("GEODIST").arg(key).arg(member1).arg(member2).arg(unit);
+
+        self.query(cmd)
+    }
+
+    /// Get the geohash strings of members, `None` for members that do not
+    /// exist.
+    pub fn geohash<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, members: M)
+            -> Response<Vec<Option<String>>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("GEOHASH").arg(key).arg(members);
+
+        self.query(cmd)
+    }
+
+    /// Find the members within an area.
+    pub fn geosearch<K: ToRedisArgs>(&mut self, key: K, search: &GeoSearch)
+            -> Response<Vec<GeoResult>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("GEOSEARCH").arg(key).arg(search.clone());
+
+        self.query(cmd)
+    }
+
+    /// Store the members within an area of `source` in `destination`,
+    /// returning their number.  With `store_dist` the distances are
+    /// stored as scores instead of the positions.  The `with_*` flags of
+    /// the search are ignored.
+    pub fn geosearchstore<D, S>(&mut self, destination: D, source: S, search: &GeoSearch,
+                                store_dist: bool) -> Response<usize>
+        where D: ToRedisArgs,
+              S: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("GEOSEARCHSTORE").arg(destination).arg(source).arg(search.search_args());
+        if store_dist {
+            cmd.arg("STOREDIST");
+        }
+
+        self.query(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn member_only() {
+        let result: GeoResult = from_redis_value(&data("Palermo")).unwrap();
+        assert_eq!(result, GeoResult {
+            member: "Palermo".to_string(),
+            dist: None,
+            hash: None,
+            coord: None,
+        });
+    }
+
+    #[test]
+    fn with_all_fields() {
+        let reply = Value::Bulk(vec![
+            data("Palermo"),
+            data("190.4424"),
+            Value::Int(3479099956230698),
+            Value::Bulk(vec![data("13.36138933897018433"), data("38.11555639549629859")]),
+        ]);
+        let result: GeoResult = from_redis_value(&reply).unwrap();
+        assert_eq!(result.member, "Palermo");
+        assert_eq!(result.dist, Some(190.4424));
+        assert_eq!(result.hash, Some(3479099956230698));
+        assert_eq!(result.coord, Some((13.36138933897018433, 38.11555639549629859)));
+    }
+
+    #[test]
+    fn with_some_fields() {
+        // Only the requested fields are sent, in a fixed order
+        let reply = Value::Bulk(vec![
+            data("Catania"),
+            Value::Bulk(vec![data("15.08726745843887329"), data("37.50266842333162032")]),
+        ]);
+        let result: GeoResult = from_redis_value(&reply).unwrap();
+        assert_eq!(result.dist, None);
+        assert_eq!(result.hash, None);
+        assert_eq!(result.coord, Some((15.08726745843887329, 37.50266842333162032)));
+    }
+
+    #[test]
+    fn malformed_result_fails() {
+        let reply = Value::Bulk(vec![
+            data("Palermo"),
+            Value::Bulk(vec![data("Catania"), Value::Nil]),
+        ]);
+        assert!(from_redis_value::<Vec<GeoResult>>(&reply).is_err());
+        assert!(from_redis_value::<GeoResult>(&Value::Bulk(vec![])).is_err());
+    }
+}
@@ -0,0 +1,429 @@
+use std::io;
+use std::time::Duration;
+
+use futures::{future, Future};
+
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+    from_redis_value,
+};
+use super::{counted_args, incr_command, millis, ExpireCondition, Ttl};
+
+/// The fields of a hash, in the order they were received or added.
+///
+/// This is the intermediate form of `HashStruct`, it is read from
+/// `HGETALL` replies and written as the arguments of `HSET`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HashFields {
+    fields: Vec<(String, Vec<u8>)>,
+    // The first field set to a value that is not a single argument,
+    // reported by `hset_struct`.
+    invalid: Option<String>,
+}
+
+/// A struct stored as the fields of a hash, see
+/// `ClientHandle::hget_struct` and `ClientHandle::hset_struct`.
+pub trait HashStruct: Sized {
+    /// Builds the struct from the fields of a hash.
+    fn from_fields(fields: &HashFields) -> RedisResult<Self>;
+
+    /// The fields to store for the struct.
+    fn to_fields(&self) -> HashFields;
+}
+
+impl HashFields {
+    pub fn new() -> HashFields {
+        HashFields::default()
+    }
+
+    /// Add a field.  Values that convert into no argument (such as
+    /// `None`) are skipped so optional fields can be set unconditionally.
+    /// Values that convert into more than one argument (such as a `Vec`)
+    /// cannot be stored in a field, `hset_struct` fails for them.
+    pub fn set<V: ToRedisArgs>(mut self, name: &str, value: V) -> HashFields {
+        let mut args = value.to_redis_args();
+        match args.len() {
+            0 => {}
+            1 => self.fields.push((name.to_string(), args.pop().unwrap())),
+            _ => {
+                if self.invalid.is_none() {
+                    self.invalid = Some(name.to_string());
+                }
+            }
+        }
+        self
+    }
+
+    /// Converts a field.  Missing fields are treated as nil so optional
+    /// fields can be read into an `Option`.
+    pub fn get<T: FromRedisValue>(&self, name: &str) -> RedisResult<T> {
+        match self.fields.iter().find(|&&(ref n, _)| n == name) {
+            Some(&(_, ref data)) => from_redis_value(&Value::Data(data.clone())),
+            None => from_redis_value(&Value::Nil),
+        }
+    }
+
+    /// The names of the fields.
+    pub fn names(&self) -> Vec<&str> {
+        self.fields.iter().map(|&(ref n, _)| &n[..]).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+impl FromRedisValue for HashFields {
+    fn from_redis_value(v: &Value) -> RedisResult<HashFields> {
+        Ok(HashFields {
+            fields: try!(from_redis_value(v)),
+            invalid: None,
+        })
+    }
+}
+
+impl ToRedisArgs for HashFields {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        for &(ref name, ref value) in self.fields.iter() {
+            rv.push(name.as_bytes().to_vec());
+            rv.push(value.clone());
+        }
+        rv
+    }
+}
+
+impl ClientHandle {
+    /// Get the value of a hash field.
+    pub fn hget<K, F, T>(&mut self, key: K, field: F) -> Response<T>
+        where K: ToRedisArgs,
+              F: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HGET").arg(key).arg(field);
+
+        self.query(cmd)
+    }
+
+    /// Get the values of multiple hash fields.  Missing fields are nil so
+    /// the reply is typically read into a `Vec<Option<_>>`.
+    pub fn hmget<K, F, T>(&mut self, key: K, fields: F) -> Response<T>
+        where K: ToRedisArgs,
+              F: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HMGET").arg(key).arg(fields);
+
+        self.query(cmd)
+    }
+
+    /// Set one or more hash fields, returning the number of fields that
+    /// were added.
+    pub fn hset<K, F, V>(&mut self, key: K, items: &[(F, V)]) -> Response<usize>
+        where K: ToRedisArgs,
+              F: ToRedisArgs,
+              V: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HSET").arg(key).arg(items);
+
+        self.query(cmd)
+    }
+
+    /// Set a hash field only if it does not exist yet.  Returns whether
+    /// the field was set.
+    pub fn hsetnx<K, F, V>(&mut self, key: K, field: F, value: V) -> Response<bool>
+        where K: ToRedisArgs,
+              F: ToRedisArgs,
+              V: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HSETNX").arg(key).arg(field).arg(value);
+
+        self.query(cmd)
+    }
+
+    /// Delete one or more hash fields, returning the number of fields
+    /// that were removed.
+    pub fn hdel<K: ToRedisArgs, F: ToRedisArgs>(&mut self, key: K, fields: F) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("HDEL").arg(key).arg(fields);
+
+        self.query(cmd)
+    }
+
+    /// Get all fields and values of a hash.  This is usually read into a
+    /// `HashMap` or `HashFields`, see `hget_struct` to read it into a
+    /// struct.
+    pub fn hgetall<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, key: K) -> Response<T> {
+        let mut cmd = Cmd::new();
+        cmd.arg("HGETALL").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Increment the value of a hash field by `delta`.
+    ///
+    /// Float deltas are sent as `HINCRBYFLOAT`, everything else as
+    /// `HINCRBY`.  The new value is returned as the type of the delta.
+    pub fn hincr_by<K, F, D>(&mut self, key: K, field: F, delta: D) -> Response<D>
+        where K: ToRedisArgs,
+              F: ToRedisArgs,
+              D: ToRedisArgs + FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg(incr_command(&delta, "HINCRBY", "HINCRBYFLOAT")).arg(key).arg(field).arg(delta);
+
+        self.query(cmd)
+    }
+
+    /// Get all field names of a hash.
+    pub fn hkeys<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, key: K) -> Response<T> {
+        let mut cmd = Cmd::new();
+        cmd.arg("HKEYS").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Get all values of a hash.
+    pub fn hvals<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, key: K) -> Response<T> {
+        let mut cmd = Cmd::new();
+        cmd.arg("HVALS").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Get the number of fields in a hash.
+    pub fn hlen<K: ToRedisArgs>(&mut self, key: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("HLEN").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Determine if a hash field exists.
+    pub fn hexists<K: ToRedisArgs, F: ToRedisArgs>(&mut self, key: K, field: F) -> Response<bool> {
+        let mut cmd = Cmd::new();
+        cmd.arg("HEXISTS").arg(key).arg(field);
+
+        self.query(cmd)
+    }
+
+    /// Get random fields of a hash.  Without a count a single field (or
+    /// nil) is returned, otherwise a list of fields.  A negative count
+    /// allows the same field to be returned multiple times.
+    pub fn hrandfield<K, T>(&mut self, key: K, count: Option<isize>) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HRANDFIELD").arg(key).arg(count);
+
+        self.query(cmd)
+    }
+
+    /// Like `hrandfield` but returns the values along with the fields,
+    /// for instance as a `Vec<(String, String)>`.
+    pub fn hrandfield_withvalues<K, T>(&mut self, key: K, count: isize) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HRANDFIELD").arg(key).arg(count).arg("WITHVALUES");
+
+        self.query(cmd)
+    }
+
+    /// Set an expiration on individual hash fields, with a resolution of
+    /// milliseconds.
+    ///
+    /// Returns a code per field: `-2` if the field does not exist, `0` if
+    /// the condition was not met, `1` if the expiration was set and `2` if
+    /// the field was deleted right away because `ttl` is zero.
+    pub fn hexpire<K, F>(&mut self, key: K, ttl: Duration, condition: Option<ExpireCondition>,
+                         fields: F) -> Response<Vec<i64>>
+        where K: ToRedisArgs,
+              F: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HPEXPIRE").arg(key).arg(millis(ttl)).arg(condition).arg("FIELDS");
+        counted_args(&mut cmd, fields);
+
+        self.query(cmd)
+    }
+
+    /// Get the remaining time to live of individual hash fields.
+    pub fn httl<K: ToRedisArgs, F: ToRedisArgs>(&mut self, key: K, fields: F)
+            -> Response<Vec<Ttl>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HTTL").arg(key).arg("FIELDS");
+        counted_args(&mut cmd, fields);
+
+        let ret = self.query::<Vec<i64>>(cmd)
+            .map(|ttls| ttls.into_iter().map(Ttl::from_secs).collect());
+
+        Box::new(ret)
+    }
+
+    /// Remove the expiration of individual hash fields.
+    ///
+    /// Returns a code per field: `-2` if the field does not exist, `-1` if
+    /// it had no expiration and `1` if the expiration was removed.
+    pub fn hpersist<K: ToRedisArgs, F: ToRedisArgs>(&mut self, key: K, fields: F)
+            -> Response<Vec<i64>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HPERSIST").arg(key).arg("FIELDS");
+        counted_args(&mut cmd, fields);
+
+        self.query(cmd)
+    }
+
+    /// Read a hash into a struct, `None` if the hash does not exist.
+    pub fn hget_struct<K, T>(&mut self, key: K) -> Response<Option<T>>
+        where K: ToRedisArgs,
+              T: HashStruct + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("HGETALL").arg(key);
+
+        self.query_with(cmd, |v| {
+            let fields: HashFields = try!(from_redis_value(v));
+            if fields.is_empty() {
+                return Ok(None);
+            }
+            T::from_fields(&fields).map(Some)
+        })
+    }
+
+    /// Store the fields of a struct in a hash.  Fields that already exist
+    /// are overwritten, other fields of the hash are kept.  Returns the
+    /// number of fields that were added.
+    ///
+    /// Fails without sending anything if there is no field to store or a
+    /// field value is not a single argument.
+    pub fn hset_struct<K, T>(&mut self, key: K, value: &T) -> Response<usize>
+        where K: ToRedisArgs,
+              T: HashStruct,
+    {
+        let fields = value.to_fields();
+        if let Some(ref name) = fields.invalid {
+            return Box::new(future::err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("hash field `{}` is not a single argument", name))));
+        }
+        if fields.is_empty() {
+            return Box::new(future::err(io::Error::new(io::ErrorKind::InvalidInput,
+                                                       "no hash fields to set")));
+        }
+
+        let mut cmd = Cmd::new();
+        cmd.arg("HSET").arg(key).arg(fields);
+
+        self.query(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct User {
+        name: String,
+        age: u32,
+        email: Option<String>,
+    }
+
+    impl HashStruct for User {
+        fn from_fields(fields: &HashFields) -> RedisResult<User> {
+            Ok(User {
+                name: try!(fields.get("name")),
+                age: try!(fields.get("age")),
+                email: try!(fields.get("email")),
+            })
+        }
+
+        fn to_fields(&self) -> HashFields {
+            HashFields::new()
+                .set("name", &self.name[..])
+                .set("age", self.age)
+                .set("email", self.email.as_ref().map(|e| &e[..]))
+        }
+    }
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    /// The `HGETALL` reply holding the arguments written for `fields`.
+    fn reply(fields: &HashFields) -> Value {
+        Value::Bulk(fields.to_redis_args().into_iter().map(Value::Data).collect())
+    }
+
+    #[test]
+    fn round_trip() {
+        let user = User { name: "ann".to_string(), age: 42, email: Some("a@b.c".to_string()) };
+        let fields = user.to_fields();
+        assert_eq!(fields.names(), vec!["name", "age", "email"]);
+
+        let read: HashFields = from_redis_value(&reply(&fields)).unwrap();
+        assert_eq!(read, fields);
+        assert_eq!(User::from_fields(&read).unwrap(), user);
+    }
+
+    #[test]
+    fn round_trip_missing_field() {
+        let user = User { name: "bob".to_string(), age: 7, email: None };
+        let fields = user.to_fields();
+        assert_eq!(fields.len(), 2);
+
+        let read: HashFields = from_redis_value(&reply(&fields)).unwrap();
+        assert_eq!(User::from_fields(&read).unwrap(), user);
+    }
+
+    #[test]
+    fn nested_pairs() {
+        let value = Value::Bulk(vec![
+            Value::Bulk(vec![data("name"), data("ann")]),
+            Value::Bulk(vec![data("age"), data("42")]),
+        ]);
+        let fields: HashFields = from_redis_value(&value).unwrap();
+        let user = User::from_fields(&fields).unwrap();
+        assert_eq!(user, User { name: "ann".to_string(), age: 42, email: None });
+    }
+
+    #[test]
+    fn binary_values() {
+        let fields = HashFields::new().set("raw", &b"\xff\x00"[..]);
+        assert_eq!(fields.to_redis_args(), vec![b"raw".to_vec(), b"\xff\x00".to_vec()]);
+        assert_eq!(fields.get::<Vec<u8>>("raw").unwrap(), b"\xff\x00".to_vec());
+    }
+
+    #[test]
+    fn multiple_args_are_invalid() {
+        let fields = HashFields::new()
+            .set("name", "ann")
+            .set("tags", vec!["a", "b"])
+            .set("pair", ("x", "y"));
+        assert_eq!(fields.names(), vec!["name"]);
+        assert_eq!(fields.invalid, Some("tags".to_string()));
+    }
+
+    #[test]
+    fn invalid_field() {
+        let value = Value::Bulk(vec![data("name"), data("ann"), data("age"), data("old")]);
+        let fields: HashFields = from_redis_value(&value).unwrap();
+        assert!(User::from_fields(&fields).is_err());
+    }
+}
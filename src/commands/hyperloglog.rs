@@ -0,0 +1,36 @@
+use {Cmd, ClientHandle, Response};
+use types::ToRedisArgs;
+
+impl ClientHandle {
+    /// Add elements to a HyperLogLog.  Returns whether an internal register
+    /// was altered, that is whether the estimated cardinality may have
+    /// changed.
+    pub fn pfadd<K: ToRedisArgs, E: ToRedisArgs>(&mut self, key: K, elements: E)
+            -> Response<bool>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("PFADD").arg(key).arg(elements);
+
+        self.query(cmd)
+    }
+
+    /// Get the estimated cardinality of a HyperLogLog.  With multiple keys
+    /// the cardinality of their union is returned.
+    pub fn pfcount<K: ToRedisArgs>(&mut self, keys: K) -> Response<u64> {
+        let mut cmd = Cmd::new();
+        cmd.arg("PFCOUNT").arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Merge HyperLogLogs into `destination`, which is included in the
+    /// union if it exists.
+    pub fn pfmerge<D: ToRedisArgs, S: ToRedisArgs>(&mut self, destination: D, sources: S)
+            -> Response<()>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("PFMERGE").arg(destination).arg(sources);
+
+        self.query(cmd)
+    }
+}
@@ -0,0 +1,400 @@
+use std::time::Duration;
+
+use futures::Future;
+
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+    from_redis_value,
+};
+use super::{millis, secs};
+
+/// Condition under which a new expiration is applied.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ExpireCondition {
+    /// Only if there is no expiration yet.
+    Nx,
+    /// Only if there is an expiration already.
+    Xx,
+    /// Only if the new expiration is later than the current one.
+    Gt,
+    /// Only if the new expiration is earlier than the current one.
+    Lt,
+}
+
+impl ToRedisArgs for ExpireCondition {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            ExpireCondition::Nx => "NX",
+            ExpireCondition::Xx => "XX",
+            ExpireCondition::Gt => "GT",
+            ExpireCondition::Lt => "LT",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// The remaining time to live of a key or hash field.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Ttl {
+    /// The key (or hash field) does not exist.
+    NoKey,
+    /// The key exists but does not expire.
+    NoExpiry,
+    /// The key expires after the given time.
+    Remaining(Duration),
+}
+
+impl Ttl {
+    /// Interprets the reply of the commands reporting seconds.
+    pub fn from_secs(reply: i64) -> Ttl {
+        match reply {
+            -2 => Ttl::NoKey,
+            -1 => Ttl::NoExpiry,
+            secs => Ttl::Remaining(Duration::from_secs(secs as u64)),
+        }
+    }
+
+    /// Interprets the reply of the commands reporting milliseconds.
+    pub fn from_millis(reply: i64) -> Ttl {
+        match reply {
+            -2 => Ttl::NoKey,
+            -1 => Ttl::NoExpiry,
+            ms => Ttl::Remaining(Duration::from_millis(ms as u64)),
+        }
+    }
+}
+
+/// The type of the value stored at a key, as reported by `TYPE`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyType {
+    /// The key does not exist.
+    None,
+    String,
+    List,
+    Set,
+    ZSet,
+    Hash,
+    Stream,
+    /// A type added by a module.
+    Other(String),
+}
+
+impl FromRedisValue for KeyType {
+    fn from_redis_value(v: &Value) -> RedisResult<KeyType> {
+        let name: String = try!(from_redis_value(v));
+        Ok(match &name[..] {
+            "none" => KeyType::None,
+            "string" => KeyType::String,
+            "list" => KeyType::List,
+            "set" => KeyType::Set,
+            "zset" => KeyType::ZSet,
+            "hash" => KeyType::Hash,
+            "stream" => KeyType::Stream,
+            _ => KeyType::Other(name),
+        })
+    }
+}
+
+/// Options for `ClientHandle::restore`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RestoreOptions {
+    ttl: u64,
+    absttl: bool,
+    replace: bool,
+    idletime: Option<Duration>,
+    freq: Option<u8>,
+}
+
+impl RestoreOptions {
+    /// Restore the key without an expiration.
+    pub fn new() -> RestoreOptions {
+        RestoreOptions::default()
+    }
+
+    /// Expire the restored key after `ttl`.
+    pub fn ttl(mut self, ttl: Duration) -> RestoreOptions {
+        self.ttl = millis(ttl);
+        self.absttl = false;
+        self
+    }
+
+    /// Expire the restored key at the given unix time in milliseconds.
+    pub fn expire_at(mut self, timestamp: u64) -> RestoreOptions {
+        self.ttl = timestamp;
+        self.absttl = true;
+        self
+    }
+
+    /// Replace the key if it already exists instead of failing.
+    pub fn replace(mut self) -> RestoreOptions {
+        self.replace = true;
+        self
+    }
+
+    /// Set the idle time used by the LRU eviction policies.
+    pub fn idletime(mut self, idle: Duration) -> RestoreOptions {
+        self.idletime = Some(idle);
+        self
+    }
+
+    /// Set the access frequency used by the LFU eviction policies.
+    pub fn freq(mut self, freq: u8) -> RestoreOptions {
+        self.freq = Some(freq);
+        self
+    }
+}
+
+impl ToRedisArgs for RestoreOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if self.replace {
+            rv.extend("REPLACE".to_redis_args());
+        }
+        if self.absttl {
+            rv.extend("ABSTTL".to_redis_args());
+        }
+        if let Some(idle) = self.idletime {
+            rv.extend(("IDLETIME", idle.as_secs()).to_redis_args());
+        }
+        if let Some(freq) = self.freq {
+            rv.extend(("FREQ", freq as usize).to_redis_args());
+        }
+        rv
+    }
+}
+
+impl ClientHandle {
+    /// Delete keys, returning the number of keys that were removed.
+    pub fn del<K: ToRedisArgs>(&mut self, keys: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("DEL").arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Like `del` but reclaims the memory in the background.
+    pub fn unlink<K: ToRedisArgs>(&mut self, keys: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("UNLINK").arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Count how many of the given keys exist.  A key passed multiple
+    /// times is counted multiple times.
+    pub fn exists<K: ToRedisArgs>(&mut self, keys: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("EXISTS").arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Expire a key after `ttl`, rounded up to whole seconds.  Returns
+    /// whether the expiration was set.
+    pub fn expire<K: ToRedisArgs>(&mut self, key: K, ttl: Duration,
+                                  condition: Option<ExpireCondition>) -> Response<bool>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("EXPIRE").arg(key).arg(secs(ttl)).arg(condition);
+
+        self.query(cmd)
+    }
+
+    /// Expire a key after `ttl`, rounded up to whole milliseconds.
+    pub fn pexpire<K: ToRedisArgs>(&mut self, key: K, ttl: Duration,
+                                   condition: Option<ExpireCondition>) -> Response<bool>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("PEXPIRE").arg(key).arg(millis(ttl)).arg(condition);
+
+        self.query(cmd)
+    }
+
+    /// Expire a key at the given unix time in seconds.
+    pub fn expireat<K: ToRedisArgs>(&mut self, key: K, timestamp: u64,
+                                    condition: Option<ExpireCondition>) -> Response<bool>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("EXPIREAT").arg(key).arg(timestamp).arg(condition);
+
+        self.query(cmd)
+    }
+
+    /// Expire a key at the given unix time in milliseconds.
+    pub fn pexpireat<K: ToRedisArgs>(&mut self, key: K, timestamp: u64,
+                                     condition: Option<ExpireCondition>) -> Response<bool>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("PEXPIREAT").arg(key).arg(timestamp).arg(condition);
+
+        self.query(cmd)
+    }
+
+    /// Get the remaining time to live of a key, with a resolution of
+    /// seconds.
+    pub fn ttl<K: ToRedisArgs>(&mut self, key: K) -> Response<Ttl> {
+        let mut cmd = Cmd::new();
+        cmd.arg("TTL").arg(key);
+
+        Box::new(self.query(cmd).map(Ttl::from_secs))
+    }
+
+    /// Get the remaining time to live of a key, with a resolution of
+    /// milliseconds.
+    pub fn pttl<K: ToRedisArgs>(&mut self, key: K) -> Response<Ttl> {
+        let mut cmd = Cmd::new();
+        cmd.arg("PTTL").arg(key);
+
+        Box::new(self.query(cmd).map(Ttl::from_millis))
+    }
+
+    /// Remove the expiration of a key.  Returns whether the key had one.
+    pub fn persist<K: ToRedisArgs>(&mut self, key: K) -> Response<bool> {
+        let mut cmd = Cmd::new();
+        cmd.arg("PERSIST").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Get the type of the value stored at a key.
+    pub fn key_type<K: ToRedisArgs>(&mut self, key: K) -> Response<KeyType> {
+        let mut cmd = Cmd::new();
+        cmd.arg("TYPE").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Rename a key, overwriting `new_key` if it exists.
+    pub fn rename<K: ToRedisArgs, N: ToRedisArgs>(&mut self, key: K, new_key: N) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("RENAME").arg(key).arg(new_key);
+
+        self.query(cmd)
+    }
+
+    /// Rename a key only if `new_key` does not exist.  Returns whether
+    /// the key was renamed.
+    pub fn renamenx<K: ToRedisArgs, N: ToRedisArgs>(&mut self, key: K, new_key: N)
+            -> Response<bool>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("RENAMENX").arg(key).arg(new_key);
+
+        self.query(cmd)
+    }
+
+    /// Copy the value of a key, optionally into another database.  Without
+    /// `replace` nothing is copied if the destination exists.  Returns
+    /// whether the value was copied.
+    pub fn copy<S, D>(&mut self, source: S, destination: D, db: Option<u32>, replace: bool)
+            -> Response<bool>
+        where S: ToRedisArgs,
+              D: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("COPY").arg(source).arg(destination);
+        if let Some(db) = db {
+            cmd.arg("DB").arg(db);
+        }
+        if replace {
+            cmd.arg("REPLACE");
+        }
+
+        self.query(cmd)
+    }
+
+    /// Move a key to another database.  Returns whether the key was
+    /// moved, which it is not if it exists in the target database.
+    pub fn move_key<K: ToRedisArgs>(&mut self, key: K, db: u32) -> Response<bool> {
+        let mut cmd = Cmd::new();
+        cmd.arg("MOVE").arg(key).arg(db);
+
+        self.query(cmd)
+    }
+
+    /// Update the last access time of keys, returning the number of keys
+    /// that exist.
+    pub fn touch<K: ToRedisArgs>(&mut self, keys: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("TOUCH").arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Get a random key, `None` if the database is empty.
+    pub fn randomkey<T: FromRedisValue + 'static>(&mut self) -> Response<Option<T>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("RANDOMKEY");
+
+        self.query(cmd)
+    }
+
+    /// Serialize the value of a key for use with `restore`.  Returns
+    /// `None` if the key does not exist.
+    pub fn dump<K: ToRedisArgs>(&mut self, key: K) -> Response<Option<Vec<u8>>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("DUMP").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Create a key from a payload created by `dump`.
+    pub fn restore<K: ToRedisArgs>(&mut self, key: K, payload: &[u8], options: &RestoreOptions)
+            -> Response<()>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("RESTORE").arg(key).arg(options.ttl).arg(payload).arg(*options);
+
+        self.query(cmd)
+    }
+
+    /// Get the internal encoding of the value of a key, such as
+    /// `listpack` or `hashtable`.
+    pub fn object_encoding<K: ToRedisArgs>(&mut self, key: K) -> Response<Option<String>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("OBJECT").arg("ENCODING").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Get the number of references to the value of a key.
+    pub fn object_refcount<K: ToRedisArgs>(&mut self, key: K) -> Response<Option<u64>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("OBJECT").arg("REFCOUNT").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Get the access frequency counter of a key.  Only available with an
+    /// LFU eviction policy.
+    pub fn object_freq<K: ToRedisArgs>(&mut self, key: K) -> Response<Option<u64>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("OBJECT").arg("FREQ").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Get the time since a key was last accessed.  Not available with an
+    /// LFU eviction policy.
+    pub fn object_idletime<K: ToRedisArgs>(&mut self, key: K) -> Response<Option<Duration>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("OBJECT").arg("IDLETIME").arg(key);
+
+        let ret = self.query::<Option<u64>>(cmd)
+            .map(|idle| idle.map(Duration::from_secs));
+
+        Box::new(ret)
+    }
+
+    /// Get the help text of the `OBJECT` subcommands.
+    pub fn object_help(&mut self) -> Response<Vec<String>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("OBJECT").arg("HELP");
+
+        self.query(cmd)
+    }
+}
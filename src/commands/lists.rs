@@ -0,0 +1,261 @@
+use {Cmd, ClientHandle, Response};
+use types::{ToRedisArgs, FromRedisValue};
+use super::counted_args;
+
+/// The end of a list an element is taken from or added to.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+impl ToRedisArgs for Direction {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            Direction::Left => "LEFT",
+            Direction::Right => "RIGHT",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// Where `LINSERT` places the new element relative to the pivot.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum InsertPosition {
+    Before,
+    After,
+}
+
+impl ToRedisArgs for InsertPosition {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            InsertPosition::Before => "BEFORE",
+            InsertPosition::After => "AFTER",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// Options for `ClientHandle::lpos`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LposOptions {
+    rank: Option<isize>,
+    count: Option<usize>,
+    maxlen: Option<usize>,
+}
+
+impl LposOptions {
+    pub fn new() -> LposOptions {
+        LposOptions::default()
+    }
+
+    /// Return the n-th match instead of the first one.  Negative ranks
+    /// search from the tail of the list.
+    pub fn rank(mut self, rank: isize) -> LposOptions {
+        self.rank = Some(rank);
+        self
+    }
+
+    /// Return up to `count` matches, zero meaning all of them.  The reply
+    /// becomes a list of positions.
+    pub fn count(mut self, count: usize) -> LposOptions {
+        self.count = Some(count);
+        self
+    }
+
+    /// Only compare the first `maxlen` elements.
+    pub fn maxlen(mut self, maxlen: usize) -> LposOptions {
+        self.maxlen = Some(maxlen);
+        self
+    }
+}
+
+impl ToRedisArgs for LposOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if let Some(rank) = self.rank {
+            rv.extend(("RANK", rank).to_redis_args());
+        }
+        if let Some(count) = self.count {
+            rv.extend(("COUNT", count).to_redis_args());
+        }
+        if let Some(maxlen) = self.maxlen {
+            rv.extend(("MAXLEN", maxlen).to_redis_args());
+        }
+        rv
+    }
+}
+
+impl ClientHandle {
+    /// Prepend one or more elements to a list, returning the new length.
+    pub fn lpush<K: ToRedisArgs, V: ToRedisArgs>(&mut self, key: K, values: V) -> Response<usize> {
+        self.push_cmd("LPUSH", key, values)
+    }
+
+    /// Append one or more elements to a list, returning the new length.
+    pub fn rpush<K: ToRedisArgs, V: ToRedisArgs>(&mut self, key: K, values: V) -> Response<usize> {
+        self.push_cmd("RPUSH", key, values)
+    }
+
+    /// Like `lpush` but only if the list already exists.
+    pub fn lpushx<K: ToRedisArgs, V: ToRedisArgs>(&mut self, key: K, values: V) -> Response<usize> {
+        self.push_cmd("LPUSHX", key, values)
+    }
+
+    /// Like `rpush` but only if the list already exists.
+    pub fn rpushx<K: ToRedisArgs, V: ToRedisArgs>(&mut self, key: K, values: V) -> Response<usize> {
+        self.push_cmd("RPUSHX", key, values)
+    }
+
+    fn push_cmd<K: ToRedisArgs, V: ToRedisArgs>(&mut self, name: &str, key: K, values: V)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg(name).arg(key).arg(values);
+
+        self.query(cmd)
+    }
+
+    /// Remove and return the first element of a list.  With a count up to
+    /// that many elements are returned as a list.
+    pub fn lpop<K, T>(&mut self, key: K, count: Option<usize>) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LPOP").arg(key).arg(count);
+
+        self.query(cmd)
+    }
+
+    /// Remove and return the last element of a list.  With a count up to
+    /// that many elements are returned as a list.
+    pub fn rpop<K, T>(&mut self, key: K, count: Option<usize>) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("RPOP").arg(key).arg(count);
+
+        self.query(cmd)
+    }
+
+    /// Get a range of elements.  Negative offsets count from the end of
+    /// the list and `stop` is inclusive.
+    pub fn lrange<K, T>(&mut self, key: K, start: isize, stop: isize) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LRANGE").arg(key).arg(start).arg(stop);
+
+        self.query(cmd)
+    }
+
+    /// Get an element by its index.
+    pub fn lindex<K, T>(&mut self, key: K, index: isize) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LINDEX").arg(key).arg(index);
+
+        self.query(cmd)
+    }
+
+    /// Set the element at an index.
+    pub fn lset<K: ToRedisArgs, V: ToRedisArgs>(&mut self, key: K, index: isize, value: V)
+            -> Response<()>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LSET").arg(key).arg(index).arg(value);
+
+        self.query(cmd)
+    }
+
+    /// Insert an element before or after the first occurrence of `pivot`.
+    /// Returns the new length of the list, `-1` if the pivot was not found
+    /// or `0` if the list does not exist.
+    pub fn linsert<K, P, V>(&mut self, key: K, position: InsertPosition, pivot: P, value: V)
+            -> Response<i64>
+        where K: ToRedisArgs,
+              P: ToRedisArgs,
+              V: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LINSERT").arg(key).arg(position).arg(pivot).arg(value);
+
+        self.query(cmd)
+    }
+
+    /// Remove the first `count` occurrences of an element, searching from
+    /// the tail if `count` is negative or removing all of them if it is
+    /// zero.  Returns the number of removed elements.
+    pub fn lrem<K: ToRedisArgs, V: ToRedisArgs>(&mut self, key: K, count: isize, value: V)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LREM").arg(key).arg(count).arg(value);
+
+        self.query(cmd)
+    }
+
+    /// Trim a list to the given range.
+    pub fn ltrim<K: ToRedisArgs>(&mut self, key: K, start: isize, stop: isize) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("LTRIM").arg(key).arg(start).arg(stop);
+
+        self.query(cmd)
+    }
+
+    /// Get the length of a list.
+    pub fn llen<K: ToRedisArgs>(&mut self, key: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("LLEN").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Find the position of an element.  The reply is an `Option<usize>`,
+    /// or a `Vec<usize>` if a count was given.
+    pub fn lpos<K, V, T>(&mut self, key: K, value: V, options: &LposOptions) -> Response<T>
+        where K: ToRedisArgs,
+              V: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LPOS").arg(key).arg(value).arg(options.clone());
+
+        self.query(cmd)
+    }
+
+    /// Move an element from one end of `src` to one end of `dst`,
+    /// returning the element.
+    pub fn lmove<S, D, T>(&mut self, src: S, dst: D, from: Direction, to: Direction)
+            -> Response<T>
+        where S: ToRedisArgs,
+              D: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LMOVE").arg(src).arg(dst).arg(from).arg(to);
+
+        self.query(cmd)
+    }
+
+    /// Pop up to `count` elements from the first non-empty list,
+    /// returning the name of the list (as bytes, key names are binary
+    /// safe) along with the elements.
+    pub fn lmpop<K, T>(&mut self, keys: K, direction: Direction, count: usize)
+            -> Response<Option<(Vec<u8>, Vec<T>)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LMPOP");
+        counted_args(&mut cmd, keys);
+        cmd.arg(direction).arg("COUNT").arg(count);
+
+        self.query(cmd)
+    }
+}
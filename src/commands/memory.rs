@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+    from_redis_value,
+};
+use super::{field, field_map};
+
+/// The memory report of `MEMORY STATS`.  Sizes are in bytes.
+///
+/// The commonly used metrics are parsed into fields, which are zero if
+/// the server does not report them.  All metrics are available through
+/// `get`.
+#[derive(Clone, Debug)]
+pub struct MemoryStats {
+    /// The peak memory consumption.
+    pub peak_allocated: u64,
+    /// The current memory consumption.
+    pub total_allocated: u64,
+    /// The memory consumed at startup.
+    pub startup_allocated: u64,
+    pub replication_backlog: u64,
+    /// The memory used by the buffers of replica connections.
+    pub clients_replicas: u64,
+    /// The memory used by the buffers of other connections.
+    pub clients_normal: u64,
+    pub aof_buffer: u64,
+    /// The memory used by cached scripts.
+    pub lua_caches: u64,
+    /// The memory used for managing the dataset and the server.
+    pub overhead_total: u64,
+    pub keys_count: u64,
+    /// The net memory usage per key.
+    pub keys_bytes_per_key: u64,
+    /// The memory used by the keys and values themselves.
+    pub dataset_bytes: u64,
+    /// `dataset_bytes` as a percentage of the net memory usage.
+    pub dataset_percentage: f64,
+    /// `total_allocated` as a percentage of `peak_allocated`.
+    pub peak_percentage: f64,
+    /// The ratio of resident to allocated memory.
+    pub fragmentation: f64,
+    /// The overhead of the hash tables per database.
+    pub databases: Vec<DbMemory>,
+    stats: HashMap<String, Value>,
+}
+
+/// The hash table overhead of a database as reported by `MEMORY STATS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DbMemory {
+    pub db: u32,
+    /// The size of the main hash table.
+    pub overhead_main: u64,
+    /// The size of the hash table tracking expirations.
+    pub overhead_expires: u64,
+}
+
+impl MemoryStats {
+    /// Converts any reported metric, for instance `allocator.resident`.
+    pub fn get<T: FromRedisValue>(&self, name: &str) -> Option<T> {
+        match self.stats.get(name) {
+            Some(v) => from_redis_value(v).ok(),
+            None => None,
+        }
+    }
+}
+
+impl FromRedisValue for MemoryStats {
+    fn from_redis_value(v: &Value) -> RedisResult<MemoryStats> {
+        let map = try!(field_map(v));
+        let int = |name: &str| -> RedisResult<u64> {
+            field::<Option<u64>>(&map, name).map(|v| v.unwrap_or(0))
+        };
+        let float = |name: &str| -> RedisResult<f64> {
+            field::<Option<f64>>(&map, name).map(|v| v.unwrap_or(0.0))
+        };
+
+        let mut databases = vec![];
+        for (name, value) in map.iter() {
+            if !name.starts_with("db.") {
+                continue;
+            }
+            let db = unwrap_or!(name["db.".len()..].parse().ok(), continue);
+            let overhead = try!(field_map(value));
+            databases.push(DbMemory {
+                db: db,
+                overhead_main: try!(field(&overhead, "overhead.hashtable.main")),
+                overhead_expires: try!(field(&overhead, "overhead.hashtable.expires")),
+            });
+        }
+        databases.sort_by_key(|db| db.db);
+
+        Ok(MemoryStats {
+            peak_allocated: try!(int("peak.allocated")),
+            total_allocated: try!(int("total.allocated")),
+            startup_allocated: try!(int("startup.allocated")),
+            replication_backlog: try!(int("replication.backlog")),
+            clients_replicas: try!(int("clients.slaves")),
+            clients_normal: try!(int("clients.normal")),
+            aof_buffer: try!(int("aof.buffer")),
+            lua_caches: try!(int("lua.caches")),
+            overhead_total: try!(int("overhead.total")),
+            keys_count: try!(int("keys.count")),
+            keys_bytes_per_key: try!(int("keys.bytes-per-key")),
+            dataset_bytes: try!(int("dataset.bytes")),
+            dataset_percentage: try!(float("dataset.percentage")),
+            peak_percentage: try!(float("peak.percentage")),
+            fragmentation: try!(float("fragmentation")),
+            databases: databases,
+            stats: map.clone(),
+        })
+    }
+}
+
+impl ClientHandle {
+    /// Get the number of bytes used by a key and its value, `None` if the
+    /// key does not exist.  Nested values are sampled, `samples` sets how
+    /// many of them (zero for all of them, the server defaults to 5).
+    pub fn memory_usage<K: ToRedisArgs>(&mut self, key: K, samples: Option<usize>)
+            -> Response<Option<u64>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("MEMORY").arg("USAGE").arg(key);
+        if let Some(samples) = samples {
+            cmd.arg("SAMPLES").arg(samples);
+        }
+
+        self.query(cmd)
+    }
+
+    /// Get a report of the memory usage of the server.
+    pub fn memory_stats(&mut self) -> Response<MemoryStats> {
+        let mut cmd = Cmd::new();
+        cmd.arg("MEMORY").arg("STATS");
+
+        self.query(cmd)
+    }
+
+    /// Get a human readable analysis of memory related issues.
+    pub fn memory_doctor(&mut self) -> Response<String> {
+        let mut cmd = Cmd::new();
+        cmd.arg("MEMORY").arg("DOCTOR");
+
+        self.query(cmd)
+    }
+
+    /// Ask the allocator to release unused memory to the operating system.
+    pub fn memory_purge(&mut self) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("MEMORY").arg("PURGE");
+
+        self.query(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    /// A reply of alternating field names and values.
+    fn map(items: Vec<(&str, Value)>) -> Value {
+        let mut rv = vec![];
+        for (name, value) in items {
+            rv.push(data(name));
+            rv.push(value);
+        }
+        Value::Bulk(rv)
+    }
+
+    fn db(main: i64, expires: i64) -> Value {
+        map(vec![
+            ("overhead.hashtable.main", Value::Int(main)),
+            ("overhead.hashtable.expires", Value::Int(expires)),
+        ])
+    }
+
+    #[test]
+    fn memory_stats() {
+        let reply = map(vec![
+            ("peak.allocated", Value::Int(2000000)),
+            ("total.allocated", Value::Int(1000000)),
+            ("startup.allocated", Value::Int(800000)),
+            ("clients.slaves", Value::Int(0)),
+            ("clients.normal", Value::Int(20000)),
+            ("db.3", db(96, 32)),
+            ("db.0", db(1024, 0)),
+            ("overhead.total", Value::Int(900000)),
+            ("keys.count", Value::Int(12)),
+            ("dataset.percentage", data("45.5")),
+            ("fragmentation", data("1.25")),
+            ("allocator.resident", Value::Int(3000000)),
+        ]);
+        let stats: MemoryStats = from_redis_value(&reply).unwrap();
+        assert_eq!(stats.peak_allocated, 2000000);
+        assert_eq!(stats.clients_normal, 20000);
+        assert_eq!(stats.keys_count, 12);
+        assert_eq!(stats.aof_buffer, 0);
+        assert_eq!(stats.dataset_percentage, 45.5);
+        assert_eq!(stats.fragmentation, 1.25);
+        assert_eq!(stats.databases, vec![
+            DbMemory { db: 0, overhead_main: 1024, overhead_expires: 0 },
+            DbMemory { db: 3, overhead_main: 96, overhead_expires: 32 },
+        ]);
+        assert_eq!(stats.get::<u64>("allocator.resident"), Some(3000000));
+        assert_eq!(stats.get::<u64>("missing"), None);
+    }
+
+    #[test]
+    fn memory_stats_unexpected_type() {
+        let reply = map(vec![("total.allocated", Value::Int(1000)), ("db.0", Value::Int(1))]);
+        assert!(from_redis_value::<MemoryStats>(&reply).is_err());
+
+        let reply = map(vec![("total.allocated", data("plenty"))]);
+        assert!(from_redis_value::<MemoryStats>(&reply).is_err());
+    }
+}
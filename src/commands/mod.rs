@@ -0,0 +1,137 @@
+//! Typed helpers for the individual redis command families.  Each module
+//! adds its commands to `ClientHandle` and defines the option and reply
+//! types those commands need.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use Cmd;
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    NumericBehavior,
+    Value,
+    RedisResult,
+    ErrorKind,
+    from_redis_value,
+};
+
+mod acl;
+mod bitmaps;
+mod client;
+mod diagnostics;
+mod functions;
+mod geo;
+mod hashes;
+mod hyperloglog;
+mod keys;
+mod lists;
+mod memory;
+mod server;
+mod sets;
+mod sorted_sets;
+mod stream_groups;
+mod streams;
+mod strings;
+
+pub use self::acl::{AclLogEntry, AclRules, AclSelector, AclUser};
+pub use self::bitmaps::{BitEncoding, BitField, BitFieldRo, BitOp, BitRange, Overflow};
+pub use self::client::{ClientInfo, ClientKillFilter, ClientType, PauseMode};
+pub use self::diagnostics::{LatencyLatest, LatencySample, SlowlogEntry};
+pub use self::functions::{FunctionInfo, FunctionLibrary, RestorePolicy};
+pub use self::geo::{GeoAddOptions, GeoResult, GeoSearch, GeoShape, GeoUnit};
+pub use self::hashes::{HashFields, HashStruct};
+pub use self::keys::{ExpireCondition, KeyType, RestoreOptions, Ttl};
+pub use self::lists::{Direction, InsertPosition, LposOptions};
+pub use self::memory::{DbMemory, MemoryStats};
+pub use self::server::{FlushMode, ShutdownOptions};
+pub use self::sorted_sets::{
+    Aggregate,
+    LexBound,
+    ScoreBound,
+    ZAddOptions,
+    ZCombineOptions,
+    ZRange,
+};
+pub use self::stream_groups::{
+    AutoClaimReply,
+    ConsumerInfo,
+    GroupInfo,
+    PendingEntry,
+    PendingSummary,
+    StreamInfo,
+    XClaimOptions,
+    XPendingRange,
+    XReadGroupOptions,
+    xreadgroup_cmd,
+};
+pub use self::streams::{
+    StreamEntry,
+    StreamId,
+    StreamKey,
+    StreamTrim,
+    XAddOptions,
+    XReadOptions,
+};
+pub use self::strings::{Expiry, SetOptions};
+
+/// Converts a duration for the arguments taking milliseconds.  Partial
+/// milliseconds are rounded up, so a short timeout does not turn into
+/// zero, which usually means "now" or "forever".
+pub fn millis(d: Duration) -> u64 {
+    d.as_secs() * 1000 + ((d.subsec_nanos() + 999_999) / 1_000_000) as u64
+}
+
+/// Converts a duration for the arguments taking seconds, rounding up
+/// partial seconds like `millis`.
+fn secs(d: Duration) -> u64 {
+    d.as_secs() + if d.subsec_nanos() > 0 { 1 } else { 0 }
+}
+
+/// Appends a list of arguments preceded by their number, as expected by
+/// the `numkeys` style arguments.
+pub fn counted_args<A: ToRedisArgs>(cmd: &mut Cmd, args: A) {
+    let args = args.to_redis_args();
+    cmd.arg(args.len());
+    for arg in args.iter() {
+        cmd.arg(&arg[..]);
+    }
+}
+
+/// Picks the integer or the float variant of an increment command based
+/// on the numeric behavior of the delta.
+fn incr_command<D: ToRedisArgs>(delta: &D, int: &'static str, float: &'static str)
+        -> &'static str
+{
+    match delta.describe_numeric_behavior() {
+        NumericBehavior::NumberIsFloat => float,
+        _ => int,
+    }
+}
+
+/// Reads a flat `name, value, name, value, ...` reply into a map so that
+/// the structured replies can pick their fields by name.
+fn field_map(v: &Value) -> RedisResult<HashMap<String, Value>> {
+    from_redis_value(v)
+}
+
+/// Converts a field of a map built by `field_map`.  Missing fields are
+/// treated as nil so optional fields can be read into an `Option`.
+fn field<T: FromRedisValue>(map: &HashMap<String, Value>, name: &str) -> RedisResult<T> {
+    from_redis_value(map.get(name).unwrap_or(&Value::Nil))
+}
+
+/// Converts every item of a list reply, failing on the first one that
+/// does not convert.  The structured replies use this instead of the
+/// default conversion of lists, which skips such items.
+fn strict_values<T: FromRedisValue>(items: &[Value]) -> RedisResult<Vec<T>> {
+    items.iter().map(from_redis_value).collect()
+}
+
+/// Fails with the same error the builtin conversions use when a reply
+/// does not have the expected shape.
+fn invalid_reply<T>(v: &Value, detail: &'static str) -> RedisResult<T> {
+    fail!((ErrorKind::TypeError,
+           "Response was of incompatible type",
+           format!("{:?} (response was {:?})", detail, v)));
+}
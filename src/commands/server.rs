@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::Future;
+
+use {Cmd, ClientHandle, Response};
+use types::{ToRedisArgs, InfoDict};
+
+/// Whether `FLUSHDB` and `FLUSHALL` free the memory before replying.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum FlushMode {
+    Sync,
+    /// Free the memory in the background.
+    Async,
+}
+
+impl ToRedisArgs for FlushMode {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            FlushMode::Sync => "SYNC",
+            FlushMode::Async => "ASYNC",
+        };
+        vec![s.as_bytes().to_vec()]
+    }
+}
+
+/// Options for `ClientHandle::shutdown`.
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownOptions {
+    save: Option<&'static str>,
+    now: bool,
+    force: bool,
+    abort: bool,
+}
+
+impl ShutdownOptions {
+    pub fn new() -> ShutdownOptions {
+        ShutdownOptions::default()
+    }
+
+    /// Save the dataset before shutting down even if no save points are
+    /// configured.
+    pub fn save(mut self) -> ShutdownOptions {
+        self.save = Some("SAVE");
+        self
+    }
+
+    /// Do not save the dataset even if save points are configured.
+    pub fn nosave(mut self) -> ShutdownOptions {
+        self.save = Some("NOSAVE");
+        self
+    }
+
+    /// Do not wait for lagging replicas.
+    pub fn now(mut self) -> ShutdownOptions {
+        self.now = true;
+        self
+    }
+
+    /// Ignore errors that would prevent the server from exiting, such as
+    /// failing to save the dataset.
+    pub fn force(mut self) -> ShutdownOptions {
+        self.force = true;
+        self
+    }
+
+    /// Cancel a shutdown that is waiting for replicas.
+    pub fn abort(mut self) -> ShutdownOptions {
+        self.abort = true;
+        self
+    }
+}
+
+impl ToRedisArgs for ShutdownOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if let Some(save) = self.save {
+            rv.extend(save.to_redis_args());
+        }
+        if self.now {
+            rv.extend("NOW".to_redis_args());
+        }
+        if self.force {
+            rv.extend("FORCE".to_redis_args());
+        }
+        if self.abort {
+            rv.extend("ABORT".to_redis_args());
+        }
+        rv
+    }
+}
+
+impl ClientHandle {
+    /// Get information about the server, either the default sections or
+    /// only the given one (such as `"memory"` or `"everything"`).
+    pub fn info(&mut self, section: Option<&str>) -> Response<InfoDict> {
+        let mut cmd = Cmd::new();
+        cmd.arg("INFO").arg(section);
+
+        self.query(cmd)
+    }
+
+    /// Get the configuration parameters matching a glob style pattern.
+    pub fn config_get<P: ToRedisArgs>(&mut self, patterns: P) -> Response<HashMap<String, String>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CONFIG").arg("GET").arg(patterns);
+
+        self.query(cmd)
+    }
+
+    /// Set configuration parameters.  Either all of them are applied or
+    /// none.
+    pub fn config_set<P: ToRedisArgs, V: ToRedisArgs>(&mut self, params: &[(P, V)])
+            -> Response<()>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("CONFIG").arg("SET").arg(params);
+
+        self.query(cmd)
+    }
+
+    /// Write the running configuration back to the configuration file.
+    pub fn config_rewrite(&mut self) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CONFIG").arg("REWRITE");
+
+        self.query(cmd)
+    }
+
+    /// Reset the statistics reported by `info`.
+    pub fn config_resetstat(&mut self) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("CONFIG").arg("RESETSTAT");
+
+        self.query(cmd)
+    }
+
+    /// Get the number of keys in the current database.
+    pub fn dbsize(&mut self) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("DBSIZE");
+
+        self.query(cmd)
+    }
+
+    /// Delete all keys of the current database.
+    pub fn flushdb(&mut self, mode: Option<FlushMode>) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("FLUSHDB").arg(mode);
+
+        self.query(cmd)
+    }
+
+    /// Delete all keys of all databases.
+    pub fn flushall(&mut self, mode: Option<FlushMode>) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("FLUSHALL").arg(mode);
+
+        self.query(cmd)
+    }
+
+    /// Get the current time of the server.
+    pub fn time(&mut self) -> Response<SystemTime> {
+        let mut cmd = Cmd::new();
+        cmd.arg("TIME");
+
+        let ret = self.query::<(u64, u32)>(cmd)
+            .map(|(secs, micros)| UNIX_EPOCH + Duration::new(secs, micros * 1000));
+
+        Box::new(ret)
+    }
+
+    /// Get the time of the last successful save to disk.
+    pub fn lastsave(&mut self) -> Response<SystemTime> {
+        let mut cmd = Cmd::new();
+        cmd.arg("LASTSAVE");
+
+        let ret = self.query::<u64>(cmd)
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+
+        Box::new(ret)
+    }
+
+    /// Save the dataset in the background.  With `schedule` the save is
+    /// postponed if an AOF rewrite is in progress instead of failing.
+    /// Returns the status message of the server.
+    pub fn bgsave(&mut self, schedule: bool) -> Response<String> {
+        let mut cmd = Cmd::new();
+        cmd.arg("BGSAVE");
+        if schedule {
+            cmd.arg("SCHEDULE");
+        }
+
+        self.query(cmd)
+    }
+
+    /// Rewrite the append only file in the background.  Returns the status
+    /// message of the server.
+    pub fn bgrewriteaof(&mut self) -> Response<String> {
+        let mut cmd = Cmd::new();
+        cmd.arg("BGREWRITEAOF");
+
+        self.query(cmd)
+    }
+
+    /// Shut the server down.
+    ///
+    /// The server closes the connection instead of replying when it exits,
+    /// so a successful shutdown resolves to an error.  Only a shutdown that
+    /// was refused or an `abort` resolve to a reply.
+    pub fn shutdown(&mut self, options: &ShutdownOptions) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SHUTDOWN").arg(options.clone());
+
+        self.query(cmd)
+    }
+
+    /// Swap two databases, clients connected to either of them see the
+    /// other's data right away.
+    pub fn swapdb(&mut self, index1: u32, index2: u32) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SWAPDB").arg(index1).arg(index2);
+
+        self.query(cmd)
+    }
+}
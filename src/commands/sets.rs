@@ -0,0 +1,168 @@
+use {Cmd, ClientHandle, Response};
+use types::{ToRedisArgs, FromRedisValue};
+use super::counted_args;
+
+impl ClientHandle {
+    /// Add one or more members to a set, returning the number of members
+    /// that were added.
+    pub fn sadd<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, members: M) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SADD").arg(key).arg(members);
+
+        self.query(cmd)
+    }
+
+    /// Remove one or more members from a set, returning the number of
+    /// members that were removed.
+    pub fn srem<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, members: M) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SREM").arg(key).arg(members);
+
+        self.query(cmd)
+    }
+
+    /// Get all members of a set, usually read into a `HashSet`.
+    pub fn smembers<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, key: K) -> Response<T> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SMEMBERS").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Determine if a value is a member of a set.
+    pub fn sismember<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, member: M)
+            -> Response<bool>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SISMEMBER").arg(key).arg(member);
+
+        self.query(cmd)
+    }
+
+    /// Determine for multiple values whether they are members of a set.
+    pub fn smismember<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, members: M)
+            -> Response<Vec<bool>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SMISMEMBER").arg(key).arg(members);
+
+        self.query(cmd)
+    }
+
+    /// Get the number of members in a set.
+    pub fn scard<K: ToRedisArgs>(&mut self, key: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SCARD").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Remove and return a random member.  With a count up to that many
+    /// members are returned as a list.
+    pub fn spop<K, T>(&mut self, key: K, count: Option<usize>) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SPOP").arg(key).arg(count);
+
+        self.query(cmd)
+    }
+
+    /// Return a random member without removing it.  With a count a list
+    /// is returned, a negative count allows repeated members.
+    pub fn srandmember<K, T>(&mut self, key: K, count: Option<isize>) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SRANDMEMBER").arg(key).arg(count);
+
+        self.query(cmd)
+    }
+
+    /// Intersect multiple sets.
+    pub fn sinter<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, keys: K) -> Response<T> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SINTER").arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Add multiple sets.
+    pub fn sunion<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, keys: K) -> Response<T> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SUNION").arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Subtract the other sets from the first one.
+    pub fn sdiff<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, keys: K) -> Response<T> {
+        let mut cmd = Cmd::new();
+        cmd.arg("SDIFF").arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Intersect multiple sets and store the result in `dst`, returning
+    /// the size of the result.
+    pub fn sinterstore<D: ToRedisArgs, K: ToRedisArgs>(&mut self, dst: D, keys: K)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SINTERSTORE").arg(dst).arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Add multiple sets and store the result in `dst`, returning the
+    /// size of the result.
+    pub fn sunionstore<D: ToRedisArgs, K: ToRedisArgs>(&mut self, dst: D, keys: K)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SUNIONSTORE").arg(dst).arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Subtract multiple sets and store the result in `dst`, returning
+    /// the size of the result.
+    pub fn sdiffstore<D: ToRedisArgs, K: ToRedisArgs>(&mut self, dst: D, keys: K)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SDIFFSTORE").arg(dst).arg(keys);
+
+        self.query(cmd)
+    }
+
+    /// Get the size of the intersection of multiple sets.  With a limit
+    /// the computation stops once the size reaches it.
+    pub fn sintercard<K: ToRedisArgs>(&mut self, keys: K, limit: Option<usize>)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SINTERCARD");
+        counted_args(&mut cmd, keys);
+        if let Some(limit) = limit {
+            cmd.arg("LIMIT").arg(limit);
+        }
+
+        self.query(cmd)
+    }
+
+    /// Move a member from one set to another.  Returns whether the member
+    /// was moved.
+    pub fn smove<S, D, M>(&mut self, src: S, dst: D, member: M) -> Response<bool>
+        where S: ToRedisArgs,
+              D: ToRedisArgs,
+              M: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SMOVE").arg(src).arg(dst).arg(member);
+
+        self.query(cmd)
+    }
+}
@@ -0,0 +1,544 @@
+use {Cmd, ClientHandle, Response};
+use types::{ToRedisArgs, FromRedisValue};
+use super::counted_args;
+
+/// A score bound for the range commands.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ScoreBound {
+    Inclusive(f64),
+    Exclusive(f64),
+    NegInf,
+    PosInf,
+}
+
+impl ToRedisArgs for ScoreBound {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let s = match *self {
+            ScoreBound::Inclusive(score) => score.to_string(),
+            ScoreBound::Exclusive(score) => format!("({}", score),
+            ScoreBound::NegInf => "-inf".to_string(),
+            ScoreBound::PosInf => "+inf".to_string(),
+        };
+        vec![s.into_bytes()]
+    }
+}
+
+/// A member bound for the lexicographical range commands.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum LexBound {
+    Inclusive(Vec<u8>),
+    Exclusive(Vec<u8>),
+    Min,
+    Max,
+}
+
+impl LexBound {
+    pub fn inclusive<V: AsRef<[u8]>>(value: V) -> LexBound {
+        LexBound::Inclusive(value.as_ref().to_vec())
+    }
+
+    pub fn exclusive<V: AsRef<[u8]>>(value: V) -> LexBound {
+        LexBound::Exclusive(value.as_ref().to_vec())
+    }
+}
+
+impl ToRedisArgs for LexBound {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let arg = match *self {
+            LexBound::Inclusive(ref value) => prefixed(b'[', value),
+            LexBound::Exclusive(ref value) => prefixed(b'(', value),
+            LexBound::Min => b"-".to_vec(),
+            LexBound::Max => b"+".to_vec(),
+        };
+        vec![arg]
+    }
+}
+
+fn prefixed(prefix: u8, value: &[u8]) -> Vec<u8> {
+    let mut rv = Vec::with_capacity(value.len() + 1);
+    rv.push(prefix);
+    rv.extend_from_slice(value);
+    rv
+}
+
+/// The range selected by `ZRANGE` and `ZRANGESTORE`.
+#[derive(Clone, Debug)]
+pub struct ZRange {
+    start: Vec<Vec<u8>>,
+    stop: Vec<Vec<u8>>,
+    by: Option<&'static str>,
+    rev: bool,
+    limit: Option<(isize, isize)>,
+}
+
+impl ZRange {
+    /// Select by rank.  Negative indexes count from the end and `stop` is
+    /// inclusive.
+    pub fn index(start: isize, stop: isize) -> ZRange {
+        ZRange::new(start.to_redis_args(), stop.to_redis_args(), None)
+    }
+
+    /// Select by score.
+    pub fn score(min: ScoreBound, max: ScoreBound) -> ZRange {
+        ZRange::new(min.to_redis_args(), max.to_redis_args(), Some("BYSCORE"))
+    }
+
+    /// Select by member, for sets where all members have the same score.
+    pub fn lex(min: LexBound, max: LexBound) -> ZRange {
+        ZRange::new(min.to_redis_args(), max.to_redis_args(), Some("BYLEX"))
+    }
+
+    fn new(start: Vec<Vec<u8>>, stop: Vec<Vec<u8>>, by: Option<&'static str>) -> ZRange {
+        ZRange {
+            start: start,
+            stop: stop,
+            by: by,
+            rev: false,
+            limit: None,
+        }
+    }
+
+    /// Return the members from the highest to the lowest score.  Score and
+    /// member bounds are still given as `(min, max)`.
+    pub fn rev(mut self) -> ZRange {
+        self.rev = true;
+        self
+    }
+
+    /// Skip `offset` members and return at most `count` of them (all if
+    /// negative).  Only valid for score and member ranges.
+    pub fn limit(mut self, offset: isize, count: isize) -> ZRange {
+        self.limit = Some((offset, count));
+        self
+    }
+}
+
+impl ToRedisArgs for ZRange {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        // With REV redis expects score and lex ranges as `max min`
+        if self.rev && self.by.is_some() {
+            rv.extend(self.stop.iter().cloned());
+            rv.extend(self.start.iter().cloned());
+        } else {
+            rv.extend(self.start.iter().cloned());
+            rv.extend(self.stop.iter().cloned());
+        }
+        if let Some(by) = self.by {
+            rv.extend(by.to_redis_args());
+        }
+        if self.rev {
+            rv.extend("REV".to_redis_args());
+        }
+        if let Some((offset, count)) = self.limit {
+            rv.extend(("LIMIT", offset, count).to_redis_args());
+        }
+        rv
+    }
+}
+
+/// Options for `ClientHandle::zadd`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZAddOptions {
+    condition: Option<&'static str>,
+    comparison: Option<&'static str>,
+    ch: bool,
+}
+
+impl ZAddOptions {
+    pub fn new() -> ZAddOptions {
+        ZAddOptions::default()
+    }
+
+    /// Only add new members, never update existing ones.
+    pub fn nx(mut self) -> ZAddOptions {
+        self.condition = Some("NX");
+        self
+    }
+
+    /// Only update existing members, never add new ones.
+    pub fn xx(mut self) -> ZAddOptions {
+        self.condition = Some("XX");
+        self
+    }
+
+    /// Only update a score if the new one is greater.
+    pub fn gt(mut self) -> ZAddOptions {
+        self.comparison = Some("GT");
+        self
+    }
+
+    /// Only update a score if the new one is lower.
+    pub fn lt(mut self) -> ZAddOptions {
+        self.comparison = Some("LT");
+        self
+    }
+
+    /// Count changed members in the reply, not only added ones.
+    pub fn ch(mut self) -> ZAddOptions {
+        self.ch = true;
+        self
+    }
+}
+
+impl ToRedisArgs for ZAddOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if let Some(condition) = self.condition {
+            rv.extend(condition.to_redis_args());
+        }
+        if let Some(comparison) = self.comparison {
+            rv.extend(comparison.to_redis_args());
+        }
+        if self.ch {
+            rv.extend("CH".to_redis_args());
+        }
+        rv
+    }
+}
+
+/// How `ZUNION` and `ZINTER` combine the scores of a member.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Aggregate {
+    Sum,
+    Min,
+    Max,
+}
+
+/// Options for combining sorted sets with `ZUNION` and `ZINTER`.
+#[derive(Clone, Debug, Default)]
+pub struct ZCombineOptions {
+    weights: Vec<f64>,
+    aggregate: Option<Aggregate>,
+}
+
+impl ZCombineOptions {
+    pub fn new() -> ZCombineOptions {
+        ZCombineOptions::default()
+    }
+
+    /// Multiply the scores of each input set by the weight at the same
+    /// position.
+    pub fn weights(mut self, weights: Vec<f64>) -> ZCombineOptions {
+        self.weights = weights;
+        self
+    }
+
+    /// How to combine the scores, the default is to sum them up.
+    pub fn aggregate(mut self, aggregate: Aggregate) -> ZCombineOptions {
+        self.aggregate = Some(aggregate);
+        self
+    }
+}
+
+impl ToRedisArgs for ZCombineOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if !self.weights.is_empty() {
+            rv.extend("WEIGHTS".to_redis_args());
+            rv.extend(self.weights.to_redis_args());
+        }
+        match self.aggregate {
+            Some(Aggregate::Sum) => rv.extend(("AGGREGATE", "SUM").to_redis_args()),
+            Some(Aggregate::Min) => rv.extend(("AGGREGATE", "MIN").to_redis_args()),
+            Some(Aggregate::Max) => rv.extend(("AGGREGATE", "MAX").to_redis_args()),
+            None => {}
+        }
+        rv
+    }
+}
+
+impl ClientHandle {
+    /// Add members with their scores, or update the scores of existing
+    /// members.  Returns the number of added (or with `ch` changed)
+    /// members.
+    pub fn zadd<K, M>(&mut self, key: K, members: &[(f64, M)], options: &ZAddOptions)
+            -> Response<usize>
+        where K: ToRedisArgs,
+              M: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZADD").arg(key).arg(options.clone()).arg(members);
+
+        self.query(cmd)
+    }
+
+    /// Increment the score of a single member like `ZINCRBY` does but
+    /// honoring the given options.  Returns the new score, or `None` if
+    /// the options prevented the update.
+    pub fn zadd_incr<K, M>(&mut self, key: K, delta: f64, member: M, options: &ZAddOptions)
+            -> Response<Option<f64>>
+        where K: ToRedisArgs,
+              M: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZADD").arg(key).arg(options.clone()).arg("INCR").arg(delta).arg(member);
+
+        self.query(cmd)
+    }
+
+    /// Increment the score of a sorted set member by `delta`, returning
+    /// the new score.  Scores are always floats so any numeric delta is
+    /// accepted.
+    pub fn zincr_by<K, M, D>(&mut self, key: K, member: M, delta: D) -> Response<f64>
+        where K: ToRedisArgs,
+              M: ToRedisArgs,
+              D: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZINCRBY").arg(key).arg(delta).arg(member);
+
+        self.query(cmd)
+    }
+
+    /// Remove one or more members, returning the number of members that
+    /// were removed.
+    pub fn zrem<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, members: M) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZREM").arg(key).arg(members);
+
+        self.query(cmd)
+    }
+
+    /// Get the number of members in a sorted set.
+    pub fn zcard<K: ToRedisArgs>(&mut self, key: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZCARD").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Get the members in a range.
+    pub fn zrange<K, T>(&mut self, key: K, range: &ZRange) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZRANGE").arg(key).arg(range.clone());
+
+        self.query(cmd)
+    }
+
+    /// Get the members in a range along with their scores.
+    pub fn zrange_withscores<K, T>(&mut self, key: K, range: &ZRange) -> Response<Vec<(T, f64)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZRANGE").arg(key).arg(range.clone()).arg("WITHSCORES");
+
+        self.query(cmd)
+    }
+
+    /// Store the members in a range of `src` in `dst`, returning the
+    /// number of stored members.
+    pub fn zrangestore<D, S>(&mut self, dst: D, src: S, range: &ZRange) -> Response<usize>
+        where D: ToRedisArgs,
+              S: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZRANGESTORE").arg(dst).arg(src).arg(range.clone());
+
+        self.query(cmd)
+    }
+
+    /// Get the rank of a member, ordered from the lowest score.
+    pub fn zrank<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, member: M)
+            -> Response<Option<usize>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZRANK").arg(key).arg(member);
+
+        self.query(cmd)
+    }
+
+    /// Get the rank of a member along with its score.
+    pub fn zrank_withscore<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, member: M)
+            -> Response<Option<(usize, f64)>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZRANK").arg(key).arg(member).arg("WITHSCORE");
+
+        self.query(cmd)
+    }
+
+    /// Get the rank of a member, ordered from the highest score.
+    pub fn zrevrank<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, member: M)
+            -> Response<Option<usize>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZREVRANK").arg(key).arg(member);
+
+        self.query(cmd)
+    }
+
+    /// Get the reverse rank of a member along with its score.
+    pub fn zrevrank_withscore<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, member: M)
+            -> Response<Option<(usize, f64)>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZREVRANK").arg(key).arg(member).arg("WITHSCORE");
+
+        self.query(cmd)
+    }
+
+    /// Get the score of a member.
+    pub fn zscore<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, member: M)
+            -> Response<Option<f64>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZSCORE").arg(key).arg(member);
+
+        self.query(cmd)
+    }
+
+    /// Get the scores of multiple members.
+    pub fn zmscore<K: ToRedisArgs, M: ToRedisArgs>(&mut self, key: K, members: M)
+            -> Response<Vec<Option<f64>>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZMSCORE").arg(key).arg(members);
+
+        self.query(cmd)
+    }
+
+    /// Remove and return the members with the lowest scores.
+    pub fn zpopmin<K, T>(&mut self, key: K, count: Option<usize>) -> Response<Vec<(T, f64)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZPOPMIN").arg(key).arg(count);
+
+        self.query(cmd)
+    }
+
+    /// Remove and return the members with the highest scores.
+    pub fn zpopmax<K, T>(&mut self, key: K, count: Option<usize>) -> Response<Vec<(T, f64)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZPOPMAX").arg(key).arg(count);
+
+        self.query(cmd)
+    }
+
+    /// Add multiple sorted sets.
+    pub fn zunion<K, T>(&mut self, keys: K, options: &ZCombineOptions) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        self.query(combine_cmd("ZUNION", None::<&str>, keys, Some(options), false))
+    }
+
+    /// Add multiple sorted sets, returning the scores along with the
+    /// members.
+    pub fn zunion_withscores<K, T>(&mut self, keys: K, options: &ZCombineOptions)
+            -> Response<Vec<(T, f64)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        self.query(combine_cmd("ZUNION", None::<&str>, keys, Some(options), true))
+    }
+
+    /// Add multiple sorted sets and store the result in `dst`, returning
+    /// the size of the result.
+    pub fn zunionstore<D, K>(&mut self, dst: D, keys: K, options: &ZCombineOptions)
+            -> Response<usize>
+        where D: ToRedisArgs,
+              K: ToRedisArgs,
+    {
+        self.query(combine_cmd("ZUNIONSTORE", Some(dst), keys, Some(options), false))
+    }
+
+    /// Intersect multiple sorted sets.
+    pub fn zinter<K, T>(&mut self, keys: K, options: &ZCombineOptions) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        self.query(combine_cmd("ZINTER", None::<&str>, keys, Some(options), false))
+    }
+
+    /// Intersect multiple sorted sets, returning the scores along with the
+    /// members.
+    pub fn zinter_withscores<K, T>(&mut self, keys: K, options: &ZCombineOptions)
+            -> Response<Vec<(T, f64)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        self.query(combine_cmd("ZINTER", None::<&str>, keys, Some(options), true))
+    }
+
+    /// Intersect multiple sorted sets and store the result in `dst`,
+    /// returning the size of the result.
+    pub fn zinterstore<D, K>(&mut self, dst: D, keys: K, options: &ZCombineOptions)
+            -> Response<usize>
+        where D: ToRedisArgs,
+              K: ToRedisArgs,
+    {
+        self.query(combine_cmd("ZINTERSTORE", Some(dst), keys, Some(options), false))
+    }
+
+    /// Subtract the other sorted sets from the first one.
+    pub fn zdiff<K, T>(&mut self, keys: K) -> Response<T>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        self.query(combine_cmd("ZDIFF", None::<&str>, keys, None, false))
+    }
+
+    /// Subtract the other sorted sets from the first one, returning the
+    /// scores along with the members.
+    pub fn zdiff_withscores<K, T>(&mut self, keys: K) -> Response<Vec<(T, f64)>>
+        where K: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        self.query(combine_cmd("ZDIFF", None::<&str>, keys, None, true))
+    }
+
+    /// Subtract multiple sorted sets and store the result in `dst`,
+    /// returning the size of the result.
+    pub fn zdiffstore<D: ToRedisArgs, K: ToRedisArgs>(&mut self, dst: D, keys: K)
+            -> Response<usize>
+    {
+        self.query(combine_cmd("ZDIFFSTORE", Some(dst), keys, None, false))
+    }
+
+    /// Count the members with a score within the given bounds.
+    pub fn zcount<K: ToRedisArgs>(&mut self, key: K, min: ScoreBound, max: ScoreBound)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZCOUNT").arg(key).arg(min).arg(max);
+
+        self.query(cmd)
+    }
+
+    /// Count the members within the given lexicographical bounds.
+    pub fn zlexcount<K: ToRedisArgs>(&mut self, key: K, min: LexBound, max: LexBound)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("ZLEXCOUNT").arg(key).arg(min).arg(max);
+
+        self.query(cmd)
+    }
+}
+
+fn combine_cmd<D, K>(name: &str, dst: Option<D>, keys: K, options: Option<&ZCombineOptions>,
+                     withscores: bool) -> Cmd
+    where D: ToRedisArgs,
+          K: ToRedisArgs,
+{
+    let mut cmd = Cmd::new();
+    cmd.arg(name).arg(dst);
+    counted_args(&mut cmd, keys);
+    if let Some(options) = options {
+        cmd.arg(options.clone());
+    }
+    if withscores {
+        cmd.arg("WITHSCORES");
+    }
+    cmd
+}
@@ -0,0 +1,831 @@
+use std::time::Duration;
+
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+    from_redis_value,
+};
+use super::{
+    field,
+    field_map,
+    invalid_reply,
+    millis,
+    strict_values,
+    StreamEntry,
+    StreamId,
+    StreamKey,
+};
+
+/// Options for `ClientHandle::xreadgroup` and `BlockingHandle::xreadgroup`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XReadGroupOptions {
+    count: Option<usize>,
+    noack: bool,
+}
+
+impl XReadGroupOptions {
+    pub fn new() -> XReadGroupOptions {
+        XReadGroupOptions::default()
+    }
+
+    /// Return at most `count` entries per stream.
+    pub fn count(mut self, count: usize) -> XReadGroupOptions {
+        self.count = Some(count);
+        self
+    }
+
+    /// Do not add the delivered entries to the pending entries list, they
+    /// are acknowledged right away.
+    pub fn noack(mut self) -> XReadGroupOptions {
+        self.noack = true;
+        self
+    }
+}
+
+impl ToRedisArgs for XReadGroupOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if let Some(count) = self.count {
+            rv.extend(("COUNT", count).to_redis_args());
+        }
+        if self.noack {
+            rv.extend("NOACK".to_redis_args());
+        }
+        rv
+    }
+}
+
+/// Builds an `XREADGROUP` command, blocking for up to `block` if given.
+pub fn xreadgroup_cmd<G, C, K, I>(group: G, consumer: C, keys: K, ids: I,
+                                  options: &XReadGroupOptions, block: Option<Duration>) -> Cmd
+    where G: ToRedisArgs,
+          C: ToRedisArgs,
+          K: ToRedisArgs,
+          I: ToRedisArgs,
+{
+    let mut cmd = Cmd::new();
+    cmd.arg("XREADGROUP").arg("GROUP").arg(group).arg(consumer).arg(*options);
+    if let Some(block) = block {
+        cmd.arg("BLOCK").arg(millis(block));
+    }
+    cmd.arg("STREAMS").arg(keys).arg(ids);
+    cmd
+}
+
+/// Options for `ClientHandle::xclaim`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XClaimOptions {
+    idle: Option<Duration>,
+    time: Option<u64>,
+    retry_count: Option<usize>,
+    force: bool,
+}
+
+impl XClaimOptions {
+    pub fn new() -> XClaimOptions {
+        XClaimOptions::default()
+    }
+
+    /// Set the idle time of the claimed entries instead of resetting it.
+    pub fn idle(mut self, idle: Duration) -> XClaimOptions {
+        self.idle = Some(idle);
+        self
+    }
+
+    /// Like `idle` but as the unix time in milliseconds of the last
+    /// delivery.
+    pub fn time(mut self, timestamp: u64) -> XClaimOptions {
+        self.time = Some(timestamp);
+        self
+    }
+
+    /// Set the delivery counter instead of incrementing it.
+    pub fn retry_count(mut self, count: usize) -> XClaimOptions {
+        self.retry_count = Some(count);
+        self
+    }
+
+    /// Create pending entries for IDs that are not pending yet.
+    pub fn force(mut self) -> XClaimOptions {
+        self.force = true;
+        self
+    }
+}
+
+impl ToRedisArgs for XClaimOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if let Some(idle) = self.idle {
+            rv.extend(("IDLE", millis(idle)).to_redis_args());
+        }
+        if let Some(time) = self.time {
+            rv.extend(("TIME", time).to_redis_args());
+        }
+        if let Some(count) = self.retry_count {
+            rv.extend(("RETRYCOUNT", count).to_redis_args());
+        }
+        if self.force {
+            rv.extend("FORCE".to_redis_args());
+        }
+        rv
+    }
+}
+
+/// The range of pending entries returned by `ClientHandle::xpending_range`.
+#[derive(Clone, Debug)]
+pub struct XPendingRange {
+    start: Vec<Vec<u8>>,
+    end: Vec<Vec<u8>>,
+    count: usize,
+    min_idle: Option<Duration>,
+    consumer: Option<Vec<Vec<u8>>>,
+}
+
+impl XPendingRange {
+    /// Return at most `count` entries with IDs between `start` and `end`,
+    /// which can be stream IDs or `-` and `+`.
+    pub fn new<S: ToRedisArgs, E: ToRedisArgs>(start: S, end: E, count: usize) -> XPendingRange {
+        XPendingRange {
+            start: start.to_redis_args(),
+            end: end.to_redis_args(),
+            count: count,
+            min_idle: None,
+            consumer: None,
+        }
+    }
+
+    /// Only return entries that have been idle for at least `min_idle`.
+    pub fn min_idle(mut self, min_idle: Duration) -> XPendingRange {
+        self.min_idle = Some(min_idle);
+        self
+    }
+
+    /// Only return entries owned by the given consumer.
+    pub fn consumer<C: ToRedisArgs>(mut self, consumer: C) -> XPendingRange {
+        self.consumer = Some(consumer.to_redis_args());
+        self
+    }
+}
+
+impl ToRedisArgs for XPendingRange {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if let Some(min_idle) = self.min_idle {
+            rv.extend(("IDLE", millis(min_idle)).to_redis_args());
+        }
+        rv.extend(self.start.iter().cloned());
+        rv.extend(self.end.iter().cloned());
+        rv.extend(self.count.to_redis_args());
+        if let Some(ref consumer) = self.consumer {
+            rv.extend(consumer.iter().cloned());
+        }
+        rv
+    }
+}
+
+/// The summary form of `XPENDING`.
+#[derive(Clone, Debug)]
+pub struct PendingSummary {
+    /// The number of pending entries.
+    pub count: usize,
+    /// The lowest pending ID.
+    pub min: Option<StreamId>,
+    /// The highest pending ID.
+    pub max: Option<StreamId>,
+    /// The number of pending entries per consumer.
+    pub consumers: Vec<(String, usize)>,
+}
+
+impl FromRedisValue for PendingSummary {
+    fn from_redis_value(v: &Value) -> RedisResult<PendingSummary> {
+        let (count, min, max, consumers): (usize, _, _, Value) = try!(from_redis_value(v));
+
+        // Without pending entries the consumers are nil
+        let consumers = match consumers {
+            Value::Nil => vec![],
+            Value::Bulk(ref items) => try!(strict_values(items)),
+            ref other => return invalid_reply(other, "Not a list of pending consumers"),
+        };
+
+        Ok(PendingSummary {
+            count: count,
+            min: min,
+            max: max,
+            consumers: consumers,
+        })
+    }
+}
+
+/// An entry of the extended form of `XPENDING`.
+#[derive(Clone, Debug)]
+pub struct PendingEntry {
+    pub id: StreamId,
+    /// The consumer owning the entry.
+    pub consumer: String,
+    /// Time since the entry was last delivered.
+    pub idle: Duration,
+    /// Number of times the entry was delivered.
+    pub deliveries: usize,
+}
+
+impl FromRedisValue for PendingEntry {
+    fn from_redis_value(v: &Value) -> RedisResult<PendingEntry> {
+        let (id, consumer, idle, deliveries): (_, _, u64, _) = try!(from_redis_value(v));
+        Ok(PendingEntry {
+            id: id,
+            consumer: consumer,
+            idle: Duration::from_millis(idle),
+            deliveries: deliveries,
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<PendingEntry>> {
+        strict_values(items)
+    }
+}
+
+/// The reply of `XAUTOCLAIM`.
+#[derive(Clone, Debug)]
+pub struct AutoClaimReply {
+    /// The ID to pass as start to continue the scan, `0-0` once the whole
+    /// pending entries list has been scanned.
+    pub next: StreamId,
+    /// The entries that were claimed.
+    pub claimed: Vec<StreamEntry>,
+    /// Pending IDs whose entries no longer exist in the stream.  They were
+    /// removed from the pending entries list.
+    pub deleted: Vec<StreamId>,
+}
+
+impl FromRedisValue for AutoClaimReply {
+    fn from_redis_value(v: &Value) -> RedisResult<AutoClaimReply> {
+        let items = match *v {
+            Value::Bulk(ref items) if items.len() == 2 || items.len() == 3 => items,
+            _ => return invalid_reply(v, "Not an XAUTOCLAIM reply"),
+        };
+
+        // Before 7.0 deleted entries are reported as nil among the claimed
+        // ones instead of in a third element.
+        let claimed = match items[1] {
+            Value::Bulk(ref entries) => {
+                try!(entries.iter()
+                     .filter(|entry| **entry != Value::Nil)
+                     .map(from_redis_value)
+                     .collect())
+            }
+            ref other => return invalid_reply(other, "Not a list of claimed entries"),
+        };
+
+        let deleted = match items.get(2) {
+            Some(deleted) => try!(from_redis_value(deleted)),
+            None => vec![],
+        };
+
+        Ok(AutoClaimReply {
+            next: try!(from_redis_value(&items[0])),
+            claimed: claimed,
+            deleted: deleted,
+        })
+    }
+}
+
+/// The reply of `XINFO STREAM`.
+#[derive(Clone, Debug)]
+pub struct StreamInfo {
+    pub length: usize,
+    pub radix_tree_keys: usize,
+    pub radix_tree_nodes: usize,
+    /// The number of consumer groups.
+    pub groups: usize,
+    pub last_generated_id: StreamId,
+    pub max_deleted_entry_id: Option<StreamId>,
+    /// All entries ever added to the stream.
+    pub entries_added: Option<u64>,
+    pub first_entry: Option<StreamEntry>,
+    pub last_entry: Option<StreamEntry>,
+}
+
+impl FromRedisValue for StreamInfo {
+    fn from_redis_value(v: &Value) -> RedisResult<StreamInfo> {
+        let map = try!(field_map(v));
+        Ok(StreamInfo {
+            length: try!(field(&map, "length")),
+            radix_tree_keys: try!(field(&map, "radix-tree-keys")),
+            radix_tree_nodes: try!(field(&map, "radix-tree-nodes")),
+            groups: try!(field(&map, "groups")),
+            last_generated_id: try!(field(&map, "last-generated-id")),
+            max_deleted_entry_id: try!(field(&map, "max-deleted-entry-id")),
+            entries_added: try!(field(&map, "entries-added")),
+            first_entry: try!(field(&map, "first-entry")),
+            last_entry: try!(field(&map, "last-entry")),
+        })
+    }
+}
+
+/// A consumer group as reported by `XINFO GROUPS`.
+#[derive(Clone, Debug)]
+pub struct GroupInfo {
+    pub name: String,
+    pub consumers: usize,
+    /// The length of the pending entries list of the group.
+    pub pending: usize,
+    pub last_delivered_id: StreamId,
+    /// The logical read counter of the group, if known.
+    pub entries_read: Option<u64>,
+    /// The number of entries not yet delivered to the group, if known.
+    pub lag: Option<u64>,
+}
+
+impl FromRedisValue for GroupInfo {
+    fn from_redis_value(v: &Value) -> RedisResult<GroupInfo> {
+        let map = try!(field_map(v));
+        Ok(GroupInfo {
+            name: try!(field(&map, "name")),
+            consumers: try!(field(&map, "consumers")),
+            pending: try!(field(&map, "pending")),
+            last_delivered_id: try!(field(&map, "last-delivered-id")),
+            entries_read: try!(field(&map, "entries-read")),
+            lag: try!(field(&map, "lag")),
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<GroupInfo>> {
+        strict_values(items)
+    }
+}
+
+/// A consumer as reported by `XINFO CONSUMERS`.
+#[derive(Clone, Debug)]
+pub struct ConsumerInfo {
+    pub name: String,
+    /// The number of entries pending for the consumer.
+    pub pending: usize,
+    /// Time since the consumer last interacted with the server.
+    pub idle: Duration,
+    /// Time since the consumer last read or claimed an entry, `None` if it
+    /// never did.
+    pub inactive: Option<Duration>,
+}
+
+impl FromRedisValue for ConsumerInfo {
+    fn from_redis_value(v: &Value) -> RedisResult<ConsumerInfo> {
+        let map = try!(field_map(v));
+        let idle: u64 = try!(field(&map, "idle"));
+        let inactive: Option<i64> = try!(field(&map, "inactive"));
+        Ok(ConsumerInfo {
+            name: try!(field(&map, "name")),
+            pending: try!(field(&map, "pending")),
+            idle: Duration::from_millis(idle),
+            inactive: match inactive {
+                Some(ms) if ms >= 0 => Some(Duration::from_millis(ms as u64)),
+                _ => None,
+            },
+        })
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<ConsumerInfo>> {
+        strict_values(items)
+    }
+}
+
+impl ClientHandle {
+    /// Create a consumer group starting at `id` (`$` for new entries only).
+    /// With `mkstream` the stream is created if it does not exist.
+    pub fn xgroup_create<K, G, I>(&mut self, key: K, group: G, id: I, mkstream: bool)
+            -> Response<()>
+        where K: ToRedisArgs,
+              G: ToRedisArgs,
+              I: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XGROUP").arg("CREATE").arg(key).arg(group).arg(id);
+        if mkstream {
+            cmd.arg("MKSTREAM");
+        }
+
+        self.query(cmd)
+    }
+
+    /// Destroy a consumer group.  Returns whether the group existed.
+    pub fn xgroup_destroy<K: ToRedisArgs, G: ToRedisArgs>(&mut self, key: K, group: G)
+            -> Response<bool>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XGROUP").arg("DESTROY").arg(key).arg(group);
+
+        self.query(cmd)
+    }
+
+    /// Set the last delivered ID of a consumer group.
+    pub fn xgroup_setid<K, G, I>(&mut self, key: K, group: G, id: I) -> Response<()>
+        where K: ToRedisArgs,
+              G: ToRedisArgs,
+              I: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XGROUP").arg("SETID").arg(key).arg(group).arg(id);
+
+        self.query(cmd)
+    }
+
+    /// Create a consumer in a group.  Returns whether it was created.
+    pub fn xgroup_createconsumer<K, G, C>(&mut self, key: K, group: G, consumer: C)
+            -> Response<bool>
+        where K: ToRedisArgs,
+              G: ToRedisArgs,
+              C: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XGROUP").arg("CREATECONSUMER").arg(key).arg(group).arg(consumer);
+
+        self.query(cmd)
+    }
+
+    /// Delete a consumer from a group, returning the number of entries
+    /// that were pending for it.
+    pub fn xgroup_delconsumer<K, G, C>(&mut self, key: K, group: G, consumer: C)
+            -> Response<usize>
+        where K: ToRedisArgs,
+              G: ToRedisArgs,
+              C: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XGROUP").arg("DELCONSUMER").arg(key).arg(group).arg(consumer);
+
+        self.query(cmd)
+    }
+
+    /// Read entries from one or more streams as a member of a consumer
+    /// group.  `>` as ID reads entries never delivered to the group, any
+    /// other ID reads the consumer's own pending entries.  To wait for new
+    /// entries use `BlockingHandle::xreadgroup`.
+    pub fn xreadgroup<G, C, K, I>(&mut self, group: G, consumer: C, keys: K, ids: I,
+                                  options: &XReadGroupOptions) -> Response<Vec<StreamKey>>
+        where G: ToRedisArgs,
+              C: ToRedisArgs,
+              K: ToRedisArgs,
+              I: ToRedisArgs,
+    {
+        let cmd = xreadgroup_cmd(group, consumer, keys, ids, options, None);
+
+        self.query_with(cmd, StreamKey::from_read_reply)
+    }
+
+    /// Acknowledge entries, returning the number of entries that were
+    /// removed from the pending entries list.
+    pub fn xack<K, G, I>(&mut self, key: K, group: G, ids: I) -> Response<usize>
+        where K: ToRedisArgs,
+              G: ToRedisArgs,
+              I: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XACK").arg(key).arg(group).arg(ids);
+
+        self.query(cmd)
+    }
+
+    /// Get a summary of the pending entries of a group.
+    pub fn xpending<K: ToRedisArgs, G: ToRedisArgs>(&mut self, key: K, group: G)
+            -> Response<PendingSummary>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XPENDING").arg(key).arg(group);
+
+        self.query(cmd)
+    }
+
+    /// Get the details of a range of pending entries.
+    pub fn xpending_range<K, G>(&mut self, key: K, group: G, range: &XPendingRange)
+            -> Response<Vec<PendingEntry>>
+        where K: ToRedisArgs,
+              G: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XPENDING").arg(key).arg(group).arg(range.clone());
+
+        self.query(cmd)
+    }
+
+    /// Transfer the ownership of pending entries idle for at least
+    /// `min_idle` to `consumer`, returning the claimed entries.
+    pub fn xclaim<K, G, C, I>(&mut self, key: K, group: G, consumer: C, min_idle: Duration,
+                              ids: I, options: &XClaimOptions) -> Response<Vec<StreamEntry>>
+        where K: ToRedisArgs,
+              G: ToRedisArgs,
+              C: ToRedisArgs,
+              I: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XCLAIM").arg(key).arg(group).arg(consumer).arg(millis(min_idle))
+            .arg(ids).arg(*options);
+
+        self.query(cmd)
+    }
+
+    /// Like `xclaim` but only returns the IDs of the claimed entries and
+    /// does not increment their delivery counter.
+    pub fn xclaim_ids<K, G, C, I>(&mut self, key: K, group: G, consumer: C, min_idle: Duration,
+                                  ids: I, options: &XClaimOptions) -> Response<Vec<StreamId>>
+        where K: ToRedisArgs,
+              G: ToRedisArgs,
+              C: ToRedisArgs,
+              I: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XCLAIM").arg(key).arg(group).arg(consumer).arg(millis(min_idle))
+            .arg(ids).arg(*options).arg("JUSTID");
+
+        self.query(cmd)
+    }
+
+    /// Scan the pending entries list from `start` and claim up to `count`
+    /// entries idle for at least `min_idle`.
+    pub fn xautoclaim<K, G, C, S>(&mut self, key: K, group: G, consumer: C, min_idle: Duration,
+                                  start: S, count: Option<usize>) -> Response<AutoClaimReply>
+        where K: ToRedisArgs,
+              G: ToRedisArgs,
+              C: ToRedisArgs,
+              S: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XAUTOCLAIM").arg(key).arg(group).arg(consumer).arg(millis(min_idle))
+            .arg(start);
+        if let Some(count) = count {
+            cmd.arg("COUNT").arg(count);
+        }
+
+        self.query(cmd)
+    }
+
+    /// Get information about a stream.
+    pub fn xinfo_stream<K: ToRedisArgs>(&mut self, key: K) -> Response<StreamInfo> {
+        let mut cmd = Cmd::new();
+        cmd.arg("XINFO").arg("STREAM").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Get the consumer groups of a stream.
+    pub fn xinfo_groups<K: ToRedisArgs>(&mut self, key: K) -> Response<Vec<GroupInfo>> {
+        let mut cmd = Cmd::new();
+        cmd.arg("XINFO").arg("GROUPS").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Get the consumers of a group.
+    pub fn xinfo_consumers<K: ToRedisArgs, G: ToRedisArgs>(&mut self, key: K, group: G)
+            -> Response<Vec<ConsumerInfo>>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XINFO").arg("CONSUMERS").arg(key).arg(group);
+
+        self.query(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    fn entry(id: &str, fields: &[&str]) -> Value {
+        Value::Bulk(vec![data(id), Value::Bulk(fields.iter().map(|f| data(f)).collect())])
+    }
+
+    /// A reply of alternating field names and values.
+    fn map(items: Vec<(&str, Value)>) -> Value {
+        let mut rv = vec![];
+        for (name, value) in items {
+            rv.push(data(name));
+            rv.push(value);
+        }
+        Value::Bulk(rv)
+    }
+
+    #[test]
+    fn pending_summary() {
+        let reply = Value::Bulk(vec![
+            Value::Int(3),
+            data("1-0"),
+            data("3-0"),
+            Value::Bulk(vec![
+                Value::Bulk(vec![data("alice"), data("2")]),
+                Value::Bulk(vec![data("bob"), data("1")]),
+            ]),
+        ]);
+        let summary: PendingSummary = from_redis_value(&reply).unwrap();
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.min, Some(StreamId::new(1, 0)));
+        assert_eq!(summary.max, Some(StreamId::new(3, 0)));
+        assert_eq!(summary.consumers, vec![("alice".to_string(), 2), ("bob".to_string(), 1)]);
+    }
+
+    #[test]
+    fn pending_summary_empty() {
+        let reply = Value::Bulk(vec![Value::Int(0), Value::Nil, Value::Nil, Value::Nil]);
+        let summary: PendingSummary = from_redis_value(&reply).unwrap();
+        assert_eq!(summary.count, 0);
+        assert_eq!(summary.min, None);
+        assert!(summary.consumers.is_empty());
+    }
+
+    #[test]
+    fn pending_summary_malformed() {
+        let reply = Value::Bulk(vec![Value::Int(1), data("1-0"), data("1-0"), Value::Int(1)]);
+        assert!(from_redis_value::<PendingSummary>(&reply).is_err());
+
+        let reply = Value::Bulk(vec![
+            Value::Int(1), data("1-0"), data("1-0"),
+            Value::Bulk(vec![Value::Bulk(vec![data("alice"), data("many")])]),
+        ]);
+        assert!(from_redis_value::<PendingSummary>(&reply).is_err());
+    }
+
+    #[test]
+    fn pending_entries() {
+        let reply = Value::Bulk(vec![
+            Value::Bulk(vec![data("1-0"), data("alice"), Value::Int(1500), Value::Int(2)]),
+            Value::Bulk(vec![data("2-0"), data("bob"), Value::Int(10), Value::Int(1)]),
+        ]);
+        let entries: Vec<PendingEntry> = from_redis_value(&reply).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, StreamId::new(1, 0));
+        assert_eq!(entries[0].consumer, "alice");
+        assert_eq!(entries[0].idle, Duration::from_millis(1500));
+        assert_eq!(entries[0].deliveries, 2);
+
+        let reply = Value::Bulk(vec![
+            Value::Bulk(vec![data("1-0"), data("alice"), Value::Int(1500), Value::Int(2)]),
+            Value::Bulk(vec![data("2-0"), data("bob")]),
+        ]);
+        assert!(from_redis_value::<Vec<PendingEntry>>(&reply).is_err());
+    }
+
+    #[test]
+    fn autoclaim_reply() {
+        // Redis 7.0 reports the deleted IDs in a third element
+        let reply = Value::Bulk(vec![
+            data("5-0"),
+            Value::Bulk(vec![entry("1-0", &["a", "1"])]),
+            Value::Bulk(vec![data("2-0")]),
+        ]);
+        let reply: AutoClaimReply = from_redis_value(&reply).unwrap();
+        assert_eq!(reply.next, StreamId::new(5, 0));
+        assert_eq!(reply.claimed.len(), 1);
+        assert_eq!(reply.claimed[0].id, StreamId::new(1, 0));
+        assert_eq!(reply.deleted, vec![StreamId::new(2, 0)]);
+    }
+
+    #[test]
+    fn autoclaim_reply_legacy() {
+        // Redis 6.2 reports deleted entries as nil among the claimed ones
+        let reply = Value::Bulk(vec![
+            data("0-0"),
+            Value::Bulk(vec![entry("1-0", &["a", "1"]), Value::Nil]),
+        ]);
+        let reply: AutoClaimReply = from_redis_value(&reply).unwrap();
+        assert_eq!(reply.next, StreamId::default());
+        assert_eq!(reply.claimed.len(), 1);
+        assert!(reply.deleted.is_empty());
+    }
+
+    #[test]
+    fn autoclaim_reply_malformed() {
+        let reply = Value::Bulk(vec![data("0-0")]);
+        assert!(from_redis_value::<AutoClaimReply>(&reply).is_err());
+
+        let reply = Value::Bulk(vec![data("0-0"), Value::Bulk(vec![data("garbage")])]);
+        assert!(from_redis_value::<AutoClaimReply>(&reply).is_err());
+
+        let reply = Value::Bulk(vec![data("0-0"), Value::Bulk(vec![]), Value::Bulk(vec![data("x")])]);
+        assert!(from_redis_value::<AutoClaimReply>(&reply).is_err());
+    }
+
+    #[test]
+    fn stream_info() {
+        let reply = map(vec![
+            ("length", Value::Int(2)),
+            ("radix-tree-keys", Value::Int(1)),
+            ("radix-tree-nodes", Value::Int(2)),
+            ("last-generated-id", data("2-0")),
+            ("max-deleted-entry-id", data("0-0")),
+            ("entries-added", Value::Int(2)),
+            ("recorded-first-entry-id", data("1-0")),
+            ("groups", Value::Int(1)),
+            ("first-entry", entry("1-0", &["a", "1"])),
+            ("last-entry", entry("2-0", &["b", "2"])),
+        ]);
+        let info: StreamInfo = from_redis_value(&reply).unwrap();
+        assert_eq!(info.length, 2);
+        assert_eq!(info.groups, 1);
+        assert_eq!(info.last_generated_id, StreamId::new(2, 0));
+        assert_eq!(info.entries_added, Some(2));
+        assert_eq!(info.last_entry.unwrap().id, StreamId::new(2, 0));
+    }
+
+    #[test]
+    fn stream_info_legacy() {
+        // Before 7.0 there is no deleted entry ID nor added entries count
+        let reply = map(vec![
+            ("length", Value::Int(0)),
+            ("radix-tree-keys", Value::Int(0)),
+            ("radix-tree-nodes", Value::Int(1)),
+            ("groups", Value::Int(0)),
+            ("last-generated-id", data("0-0")),
+            ("first-entry", Value::Nil),
+            ("last-entry", Value::Nil),
+        ]);
+        let info: StreamInfo = from_redis_value(&reply).unwrap();
+        assert_eq!(info.max_deleted_entry_id, None);
+        assert_eq!(info.entries_added, None);
+        assert!(info.first_entry.is_none());
+    }
+
+    #[test]
+    fn stream_info_malformed() {
+        let reply = map(vec![("length", data("long")), ("last-generated-id", data("0-0"))]);
+        assert!(from_redis_value::<StreamInfo>(&reply).is_err());
+    }
+
+    #[test]
+    fn group_info() {
+        let reply = Value::Bulk(vec![
+            map(vec![
+                ("name", data("workers")),
+                ("consumers", Value::Int(2)),
+                ("pending", Value::Int(3)),
+                ("last-delivered-id", data("4-0")),
+                ("entries-read", Value::Int(4)),
+                ("lag", Value::Nil),
+            ]),
+            // Before 7.0 there is no read counter nor lag
+            map(vec![
+                ("name", data("audit")),
+                ("consumers", Value::Int(0)),
+                ("pending", Value::Int(0)),
+                ("last-delivered-id", data("0-0")),
+            ]),
+        ]);
+        let groups: Vec<GroupInfo> = from_redis_value(&reply).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "workers");
+        assert_eq!(groups[0].last_delivered_id, StreamId::new(4, 0));
+        assert_eq!(groups[0].entries_read, Some(4));
+        assert_eq!(groups[0].lag, None);
+        assert_eq!(groups[1].entries_read, None);
+    }
+
+    #[test]
+    fn group_info_malformed() {
+        let reply = Value::Bulk(vec![
+            map(vec![
+                ("name", data("workers")),
+                ("consumers", Value::Int(2)),
+                ("pending", Value::Int(3)),
+                ("last-delivered-id", data("4-0")),
+            ]),
+            map(vec![("name", data("broken")), ("consumers", data("many"))]),
+        ]);
+        assert!(from_redis_value::<Vec<GroupInfo>>(&reply).is_err());
+    }
+
+    #[test]
+    fn consumer_info() {
+        let reply = Value::Bulk(vec![
+            map(vec![
+                ("name", data("alice")),
+                ("pending", Value::Int(1)),
+                ("idle", Value::Int(250)),
+                ("inactive", Value::Int(100)),
+            ]),
+            // Consumers that never read report -1, before 7.2 the field
+            // does not exist
+            map(vec![
+                ("name", data("bob")),
+                ("pending", Value::Int(0)),
+                ("idle", Value::Int(10)),
+                ("inactive", Value::Int(-1)),
+            ]),
+            map(vec![("name", data("carol")), ("pending", Value::Int(0)), ("idle", Value::Int(5))]),
+        ]);
+        let consumers: Vec<ConsumerInfo> = from_redis_value(&reply).unwrap();
+        assert_eq!(consumers.len(), 3);
+        assert_eq!(consumers[0].idle, Duration::from_millis(250));
+        assert_eq!(consumers[0].inactive, Some(Duration::from_millis(100)));
+        assert_eq!(consumers[1].inactive, None);
+        assert_eq!(consumers[2].inactive, None);
+    }
+
+    #[test]
+    fn consumer_info_malformed() {
+        let reply = Value::Bulk(vec![map(vec![("name", data("alice")), ("pending", Value::Int(1))])]);
+        assert!(from_redis_value::<Vec<ConsumerInfo>>(&reply).is_err());
+    }
+}
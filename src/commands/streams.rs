@@ -0,0 +1,452 @@
+use std::fmt;
+
+use {Cmd, ClientHandle, Response};
+use types::{
+    ToRedisArgs,
+    FromRedisValue,
+    Value,
+    RedisResult,
+    from_redis_value,
+};
+use super::{invalid_reply, strict_values};
+
+/// The ID of a stream entry, made of a millisecond timestamp and a
+/// sequence number.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug, Default)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub fn new(ms: u64, seq: u64) -> StreamId {
+        StreamId { ms: ms, seq: seq }
+    }
+
+    /// Parses an ID of the form `<ms>-<seq>`.  The sequence number may be
+    /// omitted, in which case it is zero.
+    pub fn parse(s: &str) -> Option<StreamId> {
+        let mut parts = s.splitn(2, '-');
+        let ms = unwrap_or!(parts.next().and_then(|ms| ms.parse().ok()), return None);
+        let seq = match parts.next() {
+            Some(seq) => unwrap_or!(seq.parse().ok(), return None),
+            None => 0,
+        };
+        Some(StreamId::new(ms, seq))
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl ToRedisArgs for StreamId {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        vec![self.to_string().into_bytes()]
+    }
+}
+
+impl FromRedisValue for StreamId {
+    fn from_redis_value(v: &Value) -> RedisResult<StreamId> {
+        let s: String = try!(from_redis_value(v));
+        match StreamId::parse(&s) {
+            Some(id) => Ok(id),
+            None => invalid_reply(v, "Not a stream ID"),
+        }
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<StreamId>> {
+        strict_values(items)
+    }
+}
+
+/// An entry of a stream.
+#[derive(Clone, Debug)]
+pub struct StreamEntry {
+    pub id: StreamId,
+    /// The fields in the order they were added.  A field name may occur
+    /// more than once.
+    pub fields: Vec<(String, Value)>,
+}
+
+impl StreamEntry {
+    /// Converts the value of the first field with the given name into
+    /// the given type.
+    pub fn get<T: FromRedisValue>(&self, field: &str) -> Option<T> {
+        self.fields.iter()
+            .find(|&&(ref name, _)| name == field)
+            .and_then(|&(_, ref v)| from_redis_value(v).ok())
+    }
+}
+
+impl FromRedisValue for StreamEntry {
+    fn from_redis_value(v: &Value) -> RedisResult<StreamEntry> {
+        match *v {
+            Value::Bulk(ref items) if items.len() == 2 => {
+                // Entries deleted while pending are reported without fields
+                let fields = match items[1] {
+                    Value::Nil => vec![],
+                    ref fields => try!(from_redis_value(fields)),
+                };
+                Ok(StreamEntry {
+                    id: try!(from_redis_value(&items[0])),
+                    fields: fields,
+                })
+            }
+            _ => invalid_reply(v, "Not a stream entry"),
+        }
+    }
+
+    fn from_redis_values(items: &[Value]) -> RedisResult<Vec<StreamEntry>> {
+        strict_values(items)
+    }
+}
+
+/// The entries read from one of the streams passed to `XREAD`.
+#[derive(Clone, Debug)]
+pub struct StreamKey {
+    pub key: String,
+    pub entries: Vec<StreamEntry>,
+}
+
+impl StreamKey {
+    /// Reads the per stream reply of `XREAD` and `XREADGROUP`.  That is a
+    /// list of `[key, entries]` pairs, or with RESP3 a map of key to
+    /// entries.  A nil reply (the timeout expired) yields no streams.
+    pub fn from_read_reply(v: &Value) -> RedisResult<Vec<StreamKey>> {
+        let items = match *v {
+            Value::Nil => return Ok(vec![]),
+            Value::Bulk(ref items) => items,
+            _ => return invalid_reply(v, "Not a stream read reply"),
+        };
+
+        let mut rv = vec![];
+        match items.get(0) {
+            Some(&Value::Bulk(_)) => {
+                for item in items.iter() {
+                    let (key, entries) = try!(from_redis_value(item));
+                    rv.push(StreamKey { key: key, entries: entries });
+                }
+            }
+            _ => {
+                for pair in items.chunks(2) {
+                    if pair.len() != 2 {
+                        return invalid_reply(v, "Not a stream read reply");
+                    }
+                    rv.push(StreamKey {
+                        key: try!(from_redis_value(&pair[0])),
+                        entries: try!(from_redis_value(&pair[1])),
+                    });
+                }
+            }
+        }
+        Ok(rv)
+    }
+}
+
+/// Trimming applied by `XADD` and `XTRIM`.
+#[derive(Clone, Debug)]
+pub struct StreamTrim {
+    strategy: &'static str,
+    threshold: Vec<u8>,
+    approx: bool,
+    limit: Option<usize>,
+}
+
+impl StreamTrim {
+    /// Keep at most `len` entries.
+    pub fn maxlen(len: usize) -> StreamTrim {
+        StreamTrim::new("MAXLEN", len.to_string().into_bytes())
+    }
+
+    /// Remove the entries with an ID lower than `id`.
+    pub fn minid(id: StreamId) -> StreamTrim {
+        StreamTrim::new("MINID", id.to_string().into_bytes())
+    }
+
+    fn new(strategy: &'static str, threshold: Vec<u8>) -> StreamTrim {
+        StreamTrim {
+            strategy: strategy,
+            threshold: threshold,
+            approx: false,
+            limit: None,
+        }
+    }
+
+    /// Trim lazily (`~`), which is a lot more efficient.
+    pub fn approx(mut self) -> StreamTrim {
+        self.approx = true;
+        self
+    }
+
+    /// Evict at most `limit` entries per call.  Only valid for
+    /// approximate trimming.
+    pub fn limit(mut self, limit: usize) -> StreamTrim {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl ToRedisArgs for StreamTrim {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        rv.extend(self.strategy.to_redis_args());
+        rv.extend((if self.approx { "~" } else { "=" }).to_redis_args());
+        rv.push(self.threshold.clone());
+        if let Some(limit) = self.limit {
+            rv.extend(("LIMIT", limit).to_redis_args());
+        }
+        rv
+    }
+}
+
+/// Options for `ClientHandle::xadd`.
+#[derive(Clone, Debug, Default)]
+pub struct XAddOptions {
+    nomkstream: bool,
+    trim: Option<StreamTrim>,
+    id: Option<StreamId>,
+}
+
+impl XAddOptions {
+    pub fn new() -> XAddOptions {
+        XAddOptions::default()
+    }
+
+    /// Do not create the stream if it does not exist.
+    pub fn nomkstream(mut self) -> XAddOptions {
+        self.nomkstream = true;
+        self
+    }
+
+    /// Trim the stream after adding the entry.
+    pub fn trim(mut self, trim: StreamTrim) -> XAddOptions {
+        self.trim = Some(trim);
+        self
+    }
+
+    /// Use an explicit ID instead of letting the server generate one.
+    pub fn id(mut self, id: StreamId) -> XAddOptions {
+        self.id = Some(id);
+        self
+    }
+}
+
+impl ToRedisArgs for XAddOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if self.nomkstream {
+            rv.extend("NOMKSTREAM".to_redis_args());
+        }
+        if let Some(ref trim) = self.trim {
+            rv.extend(trim.to_redis_args());
+        }
+        match self.id {
+            Some(id) => rv.extend(id.to_redis_args()),
+            None => rv.extend("*".to_redis_args()),
+        }
+        rv
+    }
+}
+
+/// Options for `ClientHandle::xread` and `BlockingHandle::xread`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XReadOptions {
+    count: Option<usize>,
+}
+
+impl XReadOptions {
+    pub fn new() -> XReadOptions {
+        XReadOptions::default()
+    }
+
+    /// Return at most `count` entries per stream.
+    pub fn count(mut self, count: usize) -> XReadOptions {
+        self.count = Some(count);
+        self
+    }
+}
+
+impl ToRedisArgs for XReadOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if let Some(count) = self.count {
+            rv.extend(("COUNT", count).to_redis_args());
+        }
+        rv
+    }
+}
+
+impl ClientHandle {
+    /// Append an entry to a stream, returning its ID.  `None` is returned
+    /// if the stream does not exist and `nomkstream` was set.
+    pub fn xadd<K, F, V>(&mut self, key: K, fields: &[(F, V)], options: &XAddOptions)
+            -> Response<Option<StreamId>>
+        where K: ToRedisArgs,
+              F: ToRedisArgs,
+              V: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XADD").arg(key).arg(options.clone()).arg(fields);
+
+        self.query(cmd)
+    }
+
+    /// Get the entries with IDs between `start` and `end`.  The bounds are
+    /// stream IDs or `-` and `+` for the smallest and largest ID, an ID
+    /// prefixed with `(` is exclusive.
+    pub fn xrange<K, S, E>(&mut self, key: K, start: S, end: E, count: Option<usize>)
+            -> Response<Vec<StreamEntry>>
+        where K: ToRedisArgs,
+              S: ToRedisArgs,
+              E: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XRANGE").arg(key).arg(start).arg(end);
+        if let Some(count) = count {
+            cmd.arg("COUNT").arg(count);
+        }
+
+        self.query(cmd)
+    }
+
+    /// Like `xrange` but in reverse order, starting from `end`.
+    pub fn xrevrange<K, E, S>(&mut self, key: K, end: E, start: S, count: Option<usize>)
+            -> Response<Vec<StreamEntry>>
+        where K: ToRedisArgs,
+              E: ToRedisArgs,
+              S: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XREVRANGE").arg(key).arg(end).arg(start);
+        if let Some(count) = count {
+            cmd.arg("COUNT").arg(count);
+        }
+
+        self.query(cmd)
+    }
+
+    /// Read the entries following the given IDs from one or more streams.
+    /// `keys` and `ids` must produce the same number of arguments, `$`
+    /// reads only entries added after the call.
+    ///
+    /// Only streams that have entries are returned.  To wait for new
+    /// entries use `BlockingHandle::xread`.
+    pub fn xread<K, I>(&mut self, keys: K, ids: I, options: &XReadOptions)
+            -> Response<Vec<StreamKey>>
+        where K: ToRedisArgs,
+              I: ToRedisArgs,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("XREAD").arg(*options).arg("STREAMS").arg(keys).arg(ids);
+
+        self.query_with(cmd, StreamKey::from_read_reply)
+    }
+
+    /// Get the number of entries in a stream.
+    pub fn xlen<K: ToRedisArgs>(&mut self, key: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("XLEN").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Delete entries, returning the number of entries that were deleted.
+    pub fn xdel<K: ToRedisArgs, I: ToRedisArgs>(&mut self, key: K, ids: I) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("XDEL").arg(key).arg(ids);
+
+        self.query(cmd)
+    }
+
+    /// Trim a stream, returning the number of entries that were deleted.
+    pub fn xtrim<K: ToRedisArgs>(&mut self, key: K, trim: &StreamTrim) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("XTRIM").arg(key).arg(trim.clone());
+
+        self.query(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser::parse_redis_value;
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    fn entry(id: &str, fields: &[&str]) -> Value {
+        Value::Bulk(vec![data(id), Value::Bulk(fields.iter().map(|f| data(f)).collect())])
+    }
+
+    #[test]
+    fn parse_stream_id() {
+        assert_eq!(StreamId::parse("1526919030474-55"), Some(StreamId::new(1526919030474, 55)));
+        assert_eq!(StreamId::parse("1526919030474"), Some(StreamId::new(1526919030474, 0)));
+        assert_eq!(StreamId::parse("abc-1"), None);
+        assert_eq!(StreamId::new(1, 2).to_string(), "1-2");
+    }
+
+    #[test]
+    fn read_reply_nested() {
+        let reply = Value::Bulk(vec![
+            Value::Bulk(vec![data("s1"), Value::Bulk(vec![entry("1-0", &["a", "1"])])]),
+            Value::Bulk(vec![data("s2"), Value::Bulk(vec![entry("2-0", &["b", "2"]),
+                                                          entry("2-1", &["c", "3"])])]),
+        ]);
+        let keys = StreamKey::from_read_reply(&reply).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].key, "s1");
+        assert_eq!(keys[0].entries[0].id, StreamId::new(1, 0));
+        assert_eq!(keys[1].key, "s2");
+        assert_eq!(keys[1].entries.len(), 2);
+        assert_eq!(keys[1].entries[1].get::<i64>("c"), Some(3));
+    }
+
+    #[test]
+    fn read_reply_map() {
+        // A RESP3 map is flattened into alternating keys and entries
+        let bytes = b"%1\r\n$2\r\ns1\r\n*1\r\n*2\r\n$3\r\n1-0\r\n*2\r\n$1\r\na\r\n$1\r\n1\r\n";
+        let reply = parse_redis_value(bytes).unwrap();
+        let keys = StreamKey::from_read_reply(&reply).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].key, "s1");
+        assert_eq!(keys[0].entries[0].get::<String>("a"), Some("1".to_string()));
+    }
+
+    #[test]
+    fn read_reply_timeout() {
+        assert!(StreamKey::from_read_reply(&Value::Nil).unwrap().is_empty());
+    }
+
+    #[test]
+    fn duplicate_fields() {
+        let entry: StreamEntry = from_redis_value(&entry("1-0", &["a", "1", "b", "2", "a", "3"]))
+            .unwrap();
+        let names: Vec<&str> = entry.fields.iter().map(|&(ref name, _)| &name[..]).collect();
+        assert_eq!(names, vec!["a", "b", "a"]);
+        assert_eq!(entry.get::<i64>("a"), Some(1));
+    }
+
+    #[test]
+    fn deleted_entry() {
+        let reply = Value::Bulk(vec![data("1-0"), Value::Nil]);
+        let entry: StreamEntry = from_redis_value(&reply).unwrap();
+        assert!(entry.fields.is_empty());
+    }
+
+    #[test]
+    fn malformed_entry_fails() {
+        let reply = Value::Bulk(vec![entry("1-0", &["a", "1"]), entry("bad", &["b", "2"])]);
+        assert!(from_redis_value::<Vec<StreamEntry>>(&reply).is_err());
+
+        let reply = Value::Bulk(vec![
+            Value::Bulk(vec![data("s1"), Value::Bulk(vec![data("garbage")])]),
+        ]);
+        assert!(StreamKey::from_read_reply(&reply).is_err());
+    }
+}
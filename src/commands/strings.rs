@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use {Cmd, ClientHandle, Response};
+use types::{ToRedisArgs, FromRedisValue};
+use super::{incr_command, millis, secs};
+
+/// Options for `ClientHandle::set_options`.
+#[derive(Clone, Debug, Default)]
+pub struct SetOptions {
+    condition: Option<&'static str>,
+    expiry: Option<SetExpiry>,
+    get: bool,
+}
+
+/// Expiration set by `SET`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SetExpiry {
+    Ex(Duration),
+    Px(Duration),
+    ExAt(u64),
+    PxAt(u64),
+    KeepTtl,
+}
+
+/// Expiration set by `GETEX`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expiry {
+    /// Expire after the given duration, rounded up to whole seconds.
+    Ex(Duration),
+    /// Expire after the given duration, rounded up to whole milliseconds.
+    Px(Duration),
+    /// Expire at the given unix time in seconds.
+    ExAt(u64),
+    /// Expire at the given unix time in milliseconds.
+    PxAt(u64),
+    /// Remove any existing expiration.
+    Persist,
+}
+
+impl SetOptions {
+    pub fn new() -> SetOptions {
+        SetOptions::default()
+    }
+
+    /// Only set the key if it does not exist yet.
+    pub fn nx(mut self) -> SetOptions {
+        self.condition = Some("NX");
+        self
+    }
+
+    /// Only set the key if it already exists.
+    pub fn xx(mut self) -> SetOptions {
+        self.condition = Some("XX");
+        self
+    }
+
+    /// Expire the key after `ttl`, rounded up to whole seconds.
+    pub fn ex(mut self, ttl: Duration) -> SetOptions {
+        self.expiry = Some(SetExpiry::Ex(ttl));
+        self
+    }
+
+    /// Expire the key after `ttl`, rounded up to whole milliseconds.
+    pub fn px(mut self, ttl: Duration) -> SetOptions {
+        self.expiry = Some(SetExpiry::Px(ttl));
+        self
+    }
+
+    /// Expire the key at the given unix time in seconds.
+    pub fn exat(mut self, timestamp: u64) -> SetOptions {
+        self.expiry = Some(SetExpiry::ExAt(timestamp));
+        self
+    }
+
+    /// Expire the key at the given unix time in milliseconds.
+    pub fn pxat(mut self, timestamp: u64) -> SetOptions {
+        self.expiry = Some(SetExpiry::PxAt(timestamp));
+        self
+    }
+
+    /// Retain the time to live of the existing key.
+    pub fn keep_ttl(mut self) -> SetOptions {
+        self.expiry = Some(SetExpiry::KeepTtl);
+        self
+    }
+
+    /// Return the old value stored at the key instead of `OK`.
+    pub fn get(mut self) -> SetOptions {
+        self.get = true;
+        self
+    }
+}
+
+impl ToRedisArgs for SetOptions {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut rv = vec![];
+        if let Some(condition) = self.condition {
+            rv.extend(condition.to_redis_args());
+        }
+        match self.expiry {
+            Some(SetExpiry::Ex(ttl)) => rv.extend(("EX", secs(ttl)).to_redis_args()),
+            Some(SetExpiry::Px(ttl)) => rv.extend(("PX", millis(ttl)).to_redis_args()),
+            Some(SetExpiry::ExAt(ts)) => rv.extend(("EXAT", ts).to_redis_args()),
+            Some(SetExpiry::PxAt(ts)) => rv.extend(("PXAT", ts).to_redis_args()),
+            Some(SetExpiry::KeepTtl) => rv.extend("KEEPTTL".to_redis_args()),
+            None => {}
+        }
+        if self.get {
+            rv.extend("GET".to_redis_args());
+        }
+        rv
+    }
+}
+
+impl ToRedisArgs for Expiry {
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        match *self {
+            Expiry::Ex(ttl) => ("EX", secs(ttl)).to_redis_args(),
+            Expiry::Px(ttl) => ("PX", millis(ttl)).to_redis_args(),
+            Expiry::ExAt(ts) => ("EXAT", ts).to_redis_args(),
+            Expiry::PxAt(ts) => ("PXAT", ts).to_redis_args(),
+            Expiry::Persist => "PERSIST".to_redis_args(),
+        }
+    }
+}
+
+impl ClientHandle {
+    /// Set the string value of a key with the given options.
+    ///
+    /// The reply is `OK` (`true`) or nil (`false`) if a `nx` or `xx`
+    /// condition was not met.  With `get` the old value is returned
+    /// instead.
+    pub fn set_options<K, V, T>(&mut self, key: K, value: V, options: &SetOptions) -> Response<T>
+        where K: ToRedisArgs,
+              V: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SET").arg(key).arg(value).arg(options.clone());
+
+        self.query(cmd)
+    }
+
+    /// Get the value of a key and change its expiration.
+    pub fn getex<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, key: K, expiry: Expiry)
+            -> Response<T>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("GETEX").arg(key).arg(expiry);
+
+        self.query(cmd)
+    }
+
+    /// Get the value of a key and delete it.
+    pub fn getdel<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, key: K) -> Response<T> {
+        let mut cmd = Cmd::new();
+        cmd.arg("GETDEL").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Overwrite part of a string starting at `offset`, returning the new
+    /// length of the string.
+    pub fn setrange<K: ToRedisArgs, V: ToRedisArgs>(&mut self, key: K, offset: usize, value: V)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("SETRANGE").arg(key).arg(offset).arg(value);
+
+        self.query(cmd)
+    }
+
+    /// Get a substring of the value of a key.  Negative offsets count
+    /// from the end of the string.
+    pub fn getrange<K: ToRedisArgs, T: FromRedisValue + 'static>(&mut self, key: K,
+                                                                 start: isize, end: isize)
+            -> Response<T>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("GETRANGE").arg(key).arg(start).arg(end);
+
+        self.query(cmd)
+    }
+
+    /// Append a value to a key, returning the new length of the string.
+    pub fn append<K: ToRedisArgs, V: ToRedisArgs>(&mut self, key: K, value: V) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("APPEND").arg(key).arg(value);
+
+        self.query(cmd)
+    }
+
+    /// Get the length of the value of a key.
+    pub fn strlen<K: ToRedisArgs>(&mut self, key: K) -> Response<usize> {
+        let mut cmd = Cmd::new();
+        cmd.arg("STRLEN").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Increment the integer value of a key by one.
+    pub fn incr<K: ToRedisArgs>(&mut self, key: K) -> Response<i64> {
+        let mut cmd = Cmd::new();
+        cmd.arg("INCR").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Increment the value of a key by `delta`.
+    ///
+    /// Float deltas are sent as `INCRBYFLOAT`, everything else as
+    /// `INCRBY`.  The new value is returned as the type of the delta.
+    pub fn incr_by<K, D>(&mut self, key: K, delta: D) -> Response<D>
+        where K: ToRedisArgs,
+              D: ToRedisArgs + FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg(incr_command(&delta, "INCRBY", "INCRBYFLOAT")).arg(key).arg(delta);
+
+        self.query(cmd)
+    }
+
+    /// Increment the float value of a key by `delta`.
+    pub fn incr_by_float<K: ToRedisArgs>(&mut self, key: K, delta: f64) -> Response<f64> {
+        let mut cmd = Cmd::new();
+        cmd.arg("INCRBYFLOAT").arg(key).arg(delta);
+
+        self.query(cmd)
+    }
+
+    /// Decrement the integer value of a key by one.
+    pub fn decr<K: ToRedisArgs>(&mut self, key: K) -> Response<i64> {
+        let mut cmd = Cmd::new();
+        cmd.arg("DECR").arg(key);
+
+        self.query(cmd)
+    }
+
+    /// Decrement the integer value of a key by `delta`.
+    pub fn decr_by<K: ToRedisArgs>(&mut self, key: K, delta: i64) -> Response<i64> {
+        let mut cmd = Cmd::new();
+        cmd.arg("DECRBY").arg(key).arg(delta);
+
+        self.query(cmd)
+    }
+
+    /// Set multiple keys to multiple values.
+    pub fn mset<K: ToRedisArgs, V: ToRedisArgs>(&mut self, items: &[(K, V)]) -> Response<()> {
+        let mut cmd = Cmd::new();
+        cmd.arg("MSET").arg(items);
+
+        self.query(cmd)
+    }
+
+    /// Set multiple keys to multiple values, only if none of the keys
+    /// exist.  Returns whether the keys were set.
+    pub fn msetnx<K: ToRedisArgs, V: ToRedisArgs>(&mut self, items: &[(K, V)]) -> Response<bool> {
+        let mut cmd = Cmd::new();
+        cmd.arg("MSETNX").arg(items);
+
+        self.query(cmd)
+    }
+
+    /// Find the longest common subsequence of the values of two keys.
+    pub fn lcs<K1, K2, T>(&mut self, key1: K1, key2: K2) -> Response<T>
+        where K1: ToRedisArgs,
+              K2: ToRedisArgs,
+              T: FromRedisValue + 'static,
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LCS").arg(key1).arg(key2);
+
+        self.query(cmd)
+    }
+
+    /// Get the length of the longest common subsequence of the values of
+    /// two keys.
+    pub fn lcs_len<K1: ToRedisArgs, K2: ToRedisArgs>(&mut self, key1: K1, key2: K2)
+            -> Response<usize>
+    {
+        let mut cmd = Cmd::new();
+        cmd.arg("LCS").arg(key1).arg(key2).arg("LEN");
+
+        self.query(cmd)
+    }
+}
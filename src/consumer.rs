@@ -0,0 +1,334 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io;
+use std::mem;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use futures::{Async, Future, Poll, Stream};
+use futures::task::{self, Task};
+use tokio_core::reactor::Handle;
+
+use {Client, ClientHandle, Response};
+use commands::{
+    xreadgroup_cmd,
+    AutoClaimReply,
+    StreamEntry,
+    StreamId,
+    StreamKey,
+    XReadGroupOptions,
+};
+
+/// Configures a `StreamConsumer`.
+#[derive(Clone, Debug)]
+pub struct ConsumerOptions {
+    key: String,
+    group: String,
+    consumer: String,
+    count: usize,
+    block: Duration,
+    autoclaim: Option<(Duration, Duration)>,
+}
+
+/// Consumes a stream as a member of a consumer group.
+///
+/// The consumer first delivers the entries still pending for it from an
+/// earlier run, then keeps reading new entries with `XREADGROUP ... BLOCK`.
+/// Every entry is yielded as a `Delivery` which has to be acknowledged
+/// once it was processed.  Acknowledgements are queued and sent with a
+/// single `XACK` before the next read.  Those still queued when the
+/// consumer is dropped are sent right away.
+///
+/// The consumer issues blocking reads and therefore owns a connection,
+/// create it with `Client::connect_consumer`.
+pub struct StreamConsumer {
+    handle: ClientHandle,
+    options: ConsumerOptions,
+    state: State,
+    buffer: VecDeque<StreamEntry>,
+    shared: Rc<Shared>,
+    history: Option<StreamId>,
+    claim_start: StreamId,
+    next_claim: Option<Instant>,
+}
+
+/// An entry delivered by a `StreamConsumer`.
+///
+/// Entries that are dropped without being acknowledged stay pending and
+/// are delivered again after a restart, or claimed by another consumer.
+pub struct Delivery {
+    entry: StreamEntry,
+    shared: Rc<Shared>,
+}
+
+/// Stops a `StreamConsumer` from another task.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    shared: Rc<Shared>,
+}
+
+/// The state shared between a consumer, its deliveries and its shutdown
+/// handles.
+struct Shared {
+    acks: RefCell<Vec<StreamId>>,
+    shutdown: Cell<bool>,
+    // Set once the consumer is dropped, acknowledgements are lost then
+    dropped: Cell<bool>,
+    // The task polling the consumer, woken by a shutdown
+    task: RefCell<Option<Task>>,
+}
+
+enum State {
+    Idle,
+    Acking(Response<usize>),
+    Claiming(Response<AutoClaimReply>),
+    Reading(Response<Vec<StreamKey>>),
+    Done,
+}
+
+impl ConsumerOptions {
+    /// Consume `key` as `consumer` of `group`.  The group has to exist,
+    /// see `ClientHandle::xgroup_create`.
+    pub fn new(key: &str, group: &str, consumer: &str) -> ConsumerOptions {
+        ConsumerOptions {
+            key: key.to_string(),
+            group: group.to_string(),
+            consumer: consumer.to_string(),
+            count: 10,
+            block: Duration::from_secs(5),
+            autoclaim: None,
+        }
+    }
+
+    /// Read at most `count` entries at a time.  Defaults to 10.
+    pub fn count(mut self, count: usize) -> ConsumerOptions {
+        self.count = count;
+        self
+    }
+
+    /// Block each read for at most `timeout`.  Defaults to five seconds.
+    /// Acknowledgements queued during a read are only sent after it, so
+    /// this also bounds how long they are delayed.
+    ///
+    /// The timeout has to be non-zero, Redis treats `BLOCK 0` as waiting
+    /// forever.
+    pub fn block(mut self, timeout: Duration) -> ConsumerOptions {
+        assert!(timeout > Duration::from_secs(0), "block timeout must be non-zero");
+        self.block = timeout;
+        self
+    }
+
+    /// Every `interval`, claim the entries that have been pending for
+    /// other consumers for at least `min_idle` with `XAUTOCLAIM`.  This
+    /// picks up the work of consumers that died.  Disabled by default.
+    ///
+    /// There is no separate timer, the claim only runs between reads.  On
+    /// an idle stream it therefore runs at most once per `block` interval.
+    pub fn autoclaim(mut self, min_idle: Duration, interval: Duration) -> ConsumerOptions {
+        self.autoclaim = Some((min_idle, interval));
+        self
+    }
+}
+
+/// Connect a client that consumes a stream.
+pub fn connect(client: Client, addr: &SocketAddr, handle: &Handle, options: ConsumerOptions)
+        -> Box<Future<Item = StreamConsumer, Error = io::Error>>
+{
+    let ret = client.connect(addr, handle)
+        .map(|handle| StreamConsumer::new(handle, options));
+
+    Box::new(ret)
+}
+
+impl StreamConsumer {
+    fn new(handle: ClientHandle, options: ConsumerOptions) -> StreamConsumer {
+        let next_claim = options.autoclaim.map(|_| Instant::now());
+        let shared = Shared {
+            acks: RefCell::new(vec![]),
+            shutdown: Cell::new(false),
+            dropped: Cell::new(false),
+            task: RefCell::new(None),
+        };
+
+        StreamConsumer {
+            handle: handle,
+            options: options,
+            state: State::Idle,
+            buffer: VecDeque::new(),
+            shared: Rc::new(shared),
+            history: Some(StreamId::default()),
+            claim_start: StreamId::default(),
+            next_claim: next_claim,
+        }
+    }
+
+    /// Get a handle to stop the consumer.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle { shared: self.shared.clone() }
+    }
+
+    fn send_acks(&mut self) -> Option<Response<usize>> {
+        let acks = mem::replace(&mut *self.shared.acks.borrow_mut(), vec![]);
+        if acks.is_empty() {
+            return None;
+        }
+        let opts = &self.options;
+        Some(self.handle.xack(&opts.key[..], &opts.group[..], acks))
+    }
+
+    /// Pick the next request to send once the previous one completed.
+    fn next_state(&mut self) -> State {
+        if let Some(f) = self.send_acks() {
+            return State::Acking(f);
+        }
+
+        if self.shared.shutdown.get() {
+            return State::Done;
+        }
+
+        if let Some((min_idle, _)) = self.options.autoclaim {
+            if self.next_claim.map_or(false, |at| at <= Instant::now()) {
+                let opts = &self.options;
+                let f = self.handle.xautoclaim(&opts.key[..], &opts.group[..],
+                                               &opts.consumer[..], min_idle,
+                                               self.claim_start, Some(opts.count));
+                return State::Claiming(f);
+            }
+        }
+
+        let opts = &self.options;
+        let (id, block) = match self.history {
+            Some(id) => (id.to_string(), None),
+            None => (">".to_string(), Some(opts.block)),
+        };
+        let cmd = xreadgroup_cmd(&opts.group[..], &opts.consumer[..], &opts.key[..], id,
+                                 &XReadGroupOptions::new().count(opts.count), block);
+        State::Reading(self.handle.query_with(cmd, StreamKey::from_read_reply))
+    }
+
+    fn on_claimed(&mut self, reply: AutoClaimReply) {
+        if reply.next == StreamId::default() {
+            let interval = self.options.autoclaim.map(|(_, interval)| interval);
+            self.next_claim = interval.map(|interval| Instant::now() + interval);
+        }
+        self.claim_start = reply.next;
+        self.buffer.extend(reply.claimed);
+    }
+
+    fn on_read(&mut self, keys: Vec<StreamKey>) {
+        let entries: Vec<StreamEntry> = keys.into_iter().flat_map(|k| k.entries).collect();
+
+        // Entries pending from an earlier run are read in pages until the
+        // first empty page, after which only new entries are requested.
+        if self.history.is_some() {
+            self.history = entries.last().map(|entry| entry.id);
+        }
+        self.buffer.extend(entries);
+    }
+}
+
+impl Stream for StreamConsumer {
+    type Item = Delivery;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Delivery>, io::Error> {
+        loop {
+            if self.shared.shutdown.get() {
+                // Undelivered entries stay pending on the server, and so do
+                // the ones of a read that is given up on
+                self.buffer.clear();
+                if let State::Reading(_) = self.state {
+                    self.state = State::Idle;
+                }
+            } else if let Some(entry) = self.buffer.pop_front() {
+                return Ok(Async::Ready(Some(Delivery {
+                    entry: entry,
+                    shared: self.shared.clone(),
+                })));
+            }
+
+            let res = match self.state {
+                State::Idle => Ok(Async::Ready(())),
+                State::Acking(ref mut f) => f.poll().map(|r| r.map(|_| ())),
+                State::Claiming(ref mut f) => match f.poll() {
+                    Ok(Async::Ready(reply)) => {
+                        self.state = State::Idle;
+                        self.on_claimed(reply);
+                        continue;
+                    }
+                    Ok(Async::NotReady) => Ok(Async::NotReady),
+                    Err(e) => Err(e),
+                },
+                State::Reading(ref mut f) => match f.poll() {
+                    Ok(Async::Ready(keys)) => {
+                        self.state = State::Idle;
+                        self.on_read(keys);
+                        continue;
+                    }
+                    Ok(Async::NotReady) => Ok(Async::NotReady),
+                    Err(e) => Err(e),
+                },
+                State::Done => return Ok(Async::Ready(None)),
+            };
+
+            match res {
+                Ok(Async::Ready(())) => self.state = self.next_state(),
+                Ok(Async::NotReady) => {
+                    *self.shared.task.borrow_mut() = Some(task::current());
+                    return Ok(Async::NotReady);
+                }
+                Err(e) => {
+                    self.state = State::Done;
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+impl Delivery {
+    /// The delivered entry.
+    pub fn entry(&self) -> &StreamEntry {
+        &self.entry
+    }
+
+    /// The ID of the delivered entry.
+    pub fn id(&self) -> StreamId {
+        self.entry.id
+    }
+
+    /// Acknowledge the entry.  The acknowledgement is sent before the
+    /// consumer's next read, when it shuts down or when it is dropped.
+    ///
+    /// Returns `false` if the consumer was dropped already, the entry
+    /// stays pending then.
+    pub fn ack(self) -> bool {
+        if self.shared.dropped.get() {
+            return false;
+        }
+        self.shared.acks.borrow_mut().push(self.entry.id);
+        true
+    }
+}
+
+impl ShutdownHandle {
+    /// Stop reading.  Queued acknowledgements are still sent, after which
+    /// the consumer's stream ends.  A blocking read in progress is given
+    /// up on, its entries are left pending.
+    pub fn shutdown(&self) {
+        self.shared.shutdown.set(true);
+        if let Some(task) = self.shared.task.borrow_mut().take() {
+            task.notify();
+        }
+    }
+}
+
+impl Drop for StreamConsumer {
+    fn drop(&mut self) {
+        self.shared.dropped.set(true);
+        // The command is queued on the connection right away, dropping
+        // the response only discards the reply
+        self.send_acks();
+    }
+}
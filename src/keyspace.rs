@@ -0,0 +1,224 @@
+use std::io;
+use std::str;
+
+use futures::{future, Async, Future, Poll, Stream};
+
+use Cmd;
+use pubsub::{Msg, PubSub};
+
+/// Configures the subscriptions made by `PubSub::keyspace_events`.
+#[derive(Clone, Debug)]
+pub struct KeyspaceOptions {
+    db: Option<u32>,
+    notify: Option<String>,
+    keyspace: bool,
+    keyevent: bool,
+}
+
+/// A change to a key reported by a keyspace notification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyspaceEvent {
+    /// The database the key lives in.
+    pub db: u32,
+    /// The key that was changed.
+    pub key: Vec<u8>,
+    /// What happened to the key.
+    pub event: KeyEvent,
+}
+
+/// The operation reported by a keyspace notification.
+///
+/// The common generic events have their own variant, everything else
+/// (mostly type specific events such as `lpush` or `hset`) is reported
+/// as `Other` with the event name as sent by the server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyEvent {
+    /// The key was deleted with `DEL` or `UNLINK`.
+    Del,
+    /// The key was written by `SET` or one of its variants.
+    Set,
+    /// A timeout was set on the key.
+    Expire,
+    /// The timeout of the key was removed.
+    Persist,
+    /// The key expired.
+    Expired,
+    /// The key was evicted because of `maxmemory`.
+    Evicted,
+    /// The key was renamed to another key.
+    RenameFrom,
+    /// The key was created by renaming another key.
+    RenameTo,
+    /// A new key was added to the database.
+    New,
+    /// Any other event.
+    Other(String),
+}
+
+/// A stream of parsed keyspace notifications.
+pub struct KeyspaceEvents {
+    pubsub: PubSub,
+}
+
+impl KeyspaceOptions {
+    /// Subscribe to keyspace notifications of all databases without
+    /// touching the server configuration.
+    pub fn new() -> KeyspaceOptions {
+        KeyspaceOptions {
+            db: None,
+            notify: None,
+            keyspace: true,
+            keyevent: false,
+        }
+    }
+
+    /// Only receive notifications for the given database.
+    pub fn db(mut self, db: u32) -> KeyspaceOptions {
+        self.db = Some(db);
+        self
+    }
+
+    /// Run `CONFIG SET notify-keyspace-events` with the given flags (for
+    /// instance `"KEA"`) before subscribing.  Notifications are disabled
+    /// by default on the server.
+    pub fn notify(mut self, flags: &str) -> KeyspaceOptions {
+        self.notify = Some(flags.to_string());
+        self
+    }
+
+    /// Subscribe to the `__keyspace@<db>__` channels.  Enabled by default.
+    pub fn keyspace(mut self, enabled: bool) -> KeyspaceOptions {
+        self.keyspace = enabled;
+        self
+    }
+
+    /// Subscribe to the `__keyevent@<db>__` channels.  Disabled by default
+    /// as every change is reported on both kinds of channels when both are
+    /// enabled in the server configuration.
+    pub fn keyevent(mut self, enabled: bool) -> KeyspaceOptions {
+        self.keyevent = enabled;
+        self
+    }
+
+    fn patterns(&self) -> Vec<String> {
+        let db = match self.db {
+            Some(db) => db.to_string(),
+            None => "*".to_string(),
+        };
+
+        let mut rv = vec![];
+        if self.keyspace {
+            rv.push(format!("__keyspace@{}__:*", db));
+        }
+        if self.keyevent {
+            rv.push(format!("__keyevent@{}__:*", db));
+        }
+        rv
+    }
+}
+
+impl PubSub {
+    /// Turn this connection into a stream of keyspace notifications.
+    pub fn keyspace_events(self, options: &KeyspaceOptions)
+            -> Box<Future<Item = KeyspaceEvents, Error = io::Error>>
+    {
+        let patterns = options.patterns();
+        if patterns.is_empty() {
+            return Box::new(future::err(io::Error::new(io::ErrorKind::InvalidInput,
+                                                       "neither keyspace nor keyevent enabled")));
+        }
+
+        let configured: Box<Future<Item = PubSub, Error = io::Error>> = match options.notify {
+            Some(ref flags) => {
+                let mut cmd = Cmd::new();
+                cmd.arg("CONFIG").arg("SET").arg("notify-keyspace-events").arg(&flags[..]);
+                self.run_cmd(cmd)
+            }
+            None => Box::new(future::ok(self)),
+        };
+
+        let ret = configured
+            .and_then(move |pubsub| pubsub.psubscribe(patterns))
+            .map(|pubsub| KeyspaceEvents { pubsub: pubsub });
+
+        Box::new(ret)
+    }
+}
+
+impl Stream for KeyspaceEvents {
+    type Item = KeyspaceEvent;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<KeyspaceEvent>, io::Error> {
+        loop {
+            let msg = match try_ready!(self.pubsub.poll()) {
+                Some(msg) => msg,
+                None => return Ok(Async::Ready(None)),
+            };
+
+            match KeyspaceEvent::from_msg(&msg) {
+                Some(event) => return Ok(Async::Ready(Some(event))),
+                None => {
+                    trace!("skipping non keyspace message; channel={:?}",
+                           String::from_utf8_lossy(msg.channel()));
+                }
+            }
+        }
+    }
+}
+
+impl KeyspaceEvent {
+    /// Parses a message received on a `__keyspace@<db>__` or
+    /// `__keyevent@<db>__` channel.
+    pub fn from_msg(msg: &Msg) -> Option<KeyspaceEvent> {
+        let payload: Vec<u8> = unwrap_or!(msg.payload().ok(), return None);
+        let channel = msg.channel();
+
+        let (is_keyspace, rest) = if channel.starts_with(b"__keyspace@") {
+            (true, &channel[b"__keyspace@".len()..])
+        } else if channel.starts_with(b"__keyevent@") {
+            (false, &channel[b"__keyevent@".len()..])
+        } else {
+            return None;
+        };
+
+        // The key may contain anything, the database is followed by the
+        // first `__:`
+        let sep = unwrap_or!(rest.windows(3).position(|w| w == b"__:"), return None);
+        let db = unwrap_or!(str::from_utf8(&rest[..sep]).ok().and_then(|db| db.parse().ok()),
+                            return None);
+        let name = &rest[sep + 3..];
+
+        let (key, event) = if is_keyspace {
+            (name.to_vec(), payload)
+        } else {
+            (payload, name.to_vec())
+        };
+        let event = KeyEvent::from_name(&unwrap_or!(String::from_utf8(event).ok(),
+                                                    return None));
+
+        Some(KeyspaceEvent {
+            db: db,
+            key: key,
+            event: event,
+        })
+    }
+}
+
+impl KeyEvent {
+    /// Maps an event name as sent by the server to a `KeyEvent`.
+    pub fn from_name(name: &str) -> KeyEvent {
+        match name {
+            "del" => KeyEvent::Del,
+            "set" => KeyEvent::Set,
+            "expire" => KeyEvent::Expire,
+            "persist" => KeyEvent::Persist,
+            "expired" => KeyEvent::Expired,
+            "evicted" => KeyEvent::Evicted,
+            "rename_from" => KeyEvent::RenameFrom,
+            "rename_to" => KeyEvent::RenameTo,
+            "new" => KeyEvent::New,
+            other => KeyEvent::Other(other.to_string()),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 #![allow(unused_imports, dead_code)]
 
+#[macro_use]
 extern crate futures;
 extern crate tokio_core;
 extern crate tokio_io;
@@ -14,27 +15,140 @@ mod macros;
 mod parser;
 mod transport;
 mod types;
+mod commands;
+mod blocking;
+mod cache;
+mod consumer;
+mod pubsub;
+mod keyspace;
+mod monitor;
 
 use std::cell::RefCell;
 use std::io;
 use std::net::SocketAddr;
+use std::rc::Rc;
 
-use futures::{Async, Future};
+use futures::{future, Async, Future};
 use tokio_core::net::TcpStream;
 use tokio_core::reactor::Handle;
 use tokio_io::{AsyncRead, AsyncWrite};
-use tokio_proto::TcpClient;
+use tokio_proto::BindClient;
 use tokio_proto::pipeline::{ClientProto, ClientService};
 use tokio_service::Service;
 
+use cache::LocalCache;
 use transport::RedisTransport;
+use types::from_redis_value;
+
+pub use blocking::BlockingHandle;
+
+pub use cache::{
+    CacheOptions,
+    CacheStats,
+    TrackingMode,
+};
 
 pub use cmd::Cmd;
 
+pub use commands::{
+    AclLogEntry,
+    AclRules,
+    AclSelector,
+    AclUser,
+    Aggregate,
+    AutoClaimReply,
+    BitEncoding,
+    BitField,
+    BitFieldRo,
+    BitOp,
+    BitRange,
+    ClientInfo,
+    ClientKillFilter,
+    ClientType,
+    ConsumerInfo,
+    DbMemory,
+    Direction,
+    ExpireCondition,
+    Expiry,
+    FlushMode,
+    FunctionInfo,
+    FunctionLibrary,
+    GeoAddOptions,
+    GeoResult,
+    GeoSearch,
+    GeoShape,
+    GeoUnit,
+    HashFields,
+    HashStruct,
+    GroupInfo,
+    InsertPosition,
+    KeyType,
+    LatencyLatest,
+    LatencySample,
+    LexBound,
+    LposOptions,
+    MemoryStats,
+    Overflow,
+    PauseMode,
+    PendingEntry,
+    PendingSummary,
+    RestoreOptions,
+    RestorePolicy,
+    ScoreBound,
+    SetOptions,
+    ShutdownOptions,
+    SlowlogEntry,
+    StreamEntry,
+    StreamId,
+    StreamInfo,
+    StreamKey,
+    StreamTrim,
+    Ttl,
+    ZAddOptions,
+    ZCombineOptions,
+    XAddOptions,
+    XClaimOptions,
+    XPendingRange,
+    XReadGroupOptions,
+    XReadOptions,
+    ZRange,
+};
+
+pub use consumer::{
+    ConsumerOptions,
+    Delivery,
+    ShutdownHandle,
+    StreamConsumer,
+};
+
+pub use keyspace::{
+    KeyEvent,
+    KeyspaceEvent,
+    KeyspaceEvents,
+    KeyspaceOptions,
+};
+
+pub use monitor::{
+    Monitor,
+    MonitorEvent,
+};
+
+pub use pubsub::{
+    Msg,
+    PubSub,
+    PubSubFuture,
+};
+
 pub use types::{
     /* low level values */
     Value,
 
+    /* structured replies */
+    CommandStats,
+    InfoDict,
+    KeyspaceInfo,
+    ReplicaInfo,
+
     /* error and result types */
     RedisError as Error,
 
@@ -49,55 +163,198 @@ pub use types::{
 };
 
 pub struct Client {
-    _private: (),
+    cache: Option<CacheOptions>,
+    name: Option<String>,
+    lib_info: Option<(String, String)>,
 }
 
 pub struct ClientHandle {
-    inner: ClientService<TcpStream, RedisProto>,
+    inner: ClientService<RedisTransport<TcpStream>, RedisProto>,
+    cache: Option<Rc<RefCell<LocalCache>>>,
 }
 
-pub type Response = Box<Future<Item = Value, Error = io::Error>>;
+pub type Response<T = Value> = Box<Future<Item = T, Error = io::Error>>;
 
+/// Binds a transport that already went through the connection handshake,
+/// so that errors replied to the handshake commands never reach the
+/// pipeline, where they would close the connection.
 struct RedisProto;
 
-impl<T: AsyncRead + AsyncWrite + 'static> ClientProto<T> for RedisProto {
+impl<T: AsyncRead + AsyncWrite + 'static> ClientProto<RedisTransport<T>> for RedisProto {
     type Request = Cmd;
     type Response = Value;
     type Transport = RedisTransport<T>;
     type BindTransport = io::Result<Self::Transport>;
 
-    fn bind_transport(&self, io: T) -> Self::BindTransport {
-        Ok(RedisTransport::new(io))
+    fn bind_transport(&self, transport: RedisTransport<T>) -> Self::BindTransport {
+        Ok(transport)
     }
 }
 
 impl Client {
     pub fn new() -> Client {
         Client {
-            _private: (),
+            cache: None,
+            name: None,
+            lib_info: None,
         }
     }
 
+    /// Name the connections with `CLIENT SETNAME` so that they can be told
+    /// apart in `CLIENT LIST`.
+    pub fn name(mut self, name: &str) -> Client {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Report the library name and version with `CLIENT SETINFO`.  Servers
+    /// older than 7.2 do not support this, the error they reply with is
+    /// ignored and the connection stays usable.
+    pub fn lib_info(mut self, name: &str, version: &str) -> Client {
+        self.lib_info = Some((name.to_string(), version.to_string()));
+        self
+    }
+
+    /// Enable client side caching of `ClientHandle::get`.
+    ///
+    /// The connection is switched to RESP3 and `CLIENT TRACKING` is turned
+    /// on.  Values fetched with a single key `get` are kept in a local LRU
+    /// and evicted when the server pushes an invalidation for the key.
+    pub fn cache(mut self, options: CacheOptions) -> Client {
+        self.cache = Some(options);
+        self
+    }
+
     pub fn connect(self, addr: &SocketAddr, handle: &Handle)
             -> Box<Future<Item = ClientHandle, Error = io::Error>>
     {
-        let ret = TcpClient::new(RedisProto)
-            .connect(addr, handle)
-            .map(|c| ClientHandle { inner: c });
+        let (setname, setinfo) = self.setup_cmds();
 
-        Box::new(ret)
+        match self.cache {
+            Some(options) => cache::connect(addr, handle, options, setname, setinfo),
+            None => {
+                let handle = handle.clone();
+                let ret = TcpStream::connect(addr, &handle)
+                    .and_then(move |io| {
+                        transport::handshake(RedisTransport::new(io), setname, setinfo)
+                    })
+                    .map(move |transport| ClientHandle::bind(transport, &handle, None));
+
+                Box::new(ret)
+            }
+        }
+    }
+
+    /// Open a dedicated connection for the blocking commands.
+    pub fn connect_blocking(self, addr: &SocketAddr, handle: &Handle)
+            -> Box<Future<Item = BlockingHandle, Error = io::Error>>
+    {
+        blocking::connect(self, addr, handle)
+    }
+
+    /// Open a dedicated connection consuming a stream as a member of a
+    /// consumer group.
+    pub fn connect_consumer(self, addr: &SocketAddr, handle: &Handle, options: ConsumerOptions)
+            -> Box<Future<Item = StreamConsumer, Error = io::Error>>
+    {
+        consumer::connect(self, addr, handle, options)
+    }
+
+    /// Open a dedicated connection for subscribing to channels.
+    pub fn connect_pubsub(self, addr: &SocketAddr, handle: &Handle)
+            -> Box<Future<Item = PubSub, Error = io::Error>>
+    {
+        pubsub::connect(self, addr, handle)
+    }
+
+    /// Open a dedicated connection receiving every command processed by
+    /// the server.
+    pub fn connect_monitor(self, addr: &SocketAddr, handle: &Handle)
+            -> Box<Future<Item = Monitor, Error = io::Error>>
+    {
+        monitor::connect(self, addr, handle)
+    }
+
+    /// The commands naming a new connection, split into those that have to
+    /// succeed and those whose errors are ignored.
+    fn setup_cmds(&self) -> (Vec<Cmd>, Vec<Cmd>) {
+        let mut setname = vec![];
+        if let Some(ref name) = self.name {
+            let mut cmd = Cmd::new();
+            cmd.arg("CLIENT").arg("SETNAME").arg(&name[..]);
+            setname.push(cmd);
+        }
+
+        let mut setinfo = vec![];
+        if let Some((ref name, ref version)) = self.lib_info {
+            let mut cmd = Cmd::new();
+            cmd.arg("CLIENT").arg("SETINFO").arg("LIB-NAME").arg(&name[..]);
+            setinfo.push(cmd);
+
+            let mut cmd = Cmd::new();
+            cmd.arg("CLIENT").arg("SETINFO").arg("LIB-VER").arg(&version[..]);
+            setinfo.push(cmd);
+        }
+
+        (setname, setinfo)
     }
 }
 
 impl ClientHandle {
+    /// Start the pipeline on a transport whose handshake is done.
+    fn bind(transport: RedisTransport<TcpStream>,
+            handle: &Handle,
+            cache: Option<Rc<RefCell<LocalCache>>>) -> ClientHandle
+    {
+        ClientHandle {
+            inner: RedisProto.bind_client(handle, transport),
+            cache: cache,
+        }
+    }
+
     /// Get the value of a key.  If key is a vec this becomes an `MGET`.
+    ///
+    /// With client side caching enabled single keys are served from the
+    /// local cache when possible.
     pub fn get<K: ToRedisArgs>(&mut self, key: K) -> Response {
+        if key.is_single_arg() {
+            if let Some(cache) = self.cache.clone() {
+                if cache.borrow().is_enabled() {
+                    return self.get_cached(cache, key.to_redis_args().remove(0));
+                }
+            }
+        }
+
         let mut cmd = Cmd::new();
         cmd.arg(if key.is_single_arg() { "GET" } else { "MGET" }).arg(key);
 
         self.call(cmd)
     }
 
+    fn get_cached(&self, cache: Rc<RefCell<LocalCache>>, key: Vec<u8>) -> Response {
+        if let Some(val) = cache.borrow_mut().lookup(&key) {
+            return Box::new(future::ok(val));
+        }
+
+        let mut cmd = Cmd::new();
+        cmd.arg("GET").arg(&key[..]);
+
+        let ret = self.call(cmd).then(move |res| {
+            match res {
+                Ok(ref val) => cache.borrow_mut().complete(key, val.clone()),
+                Err(_) => cache.borrow_mut().abort(&key),
+            }
+            res
+        });
+
+        Box::new(ret)
+    }
+
+    /// Hit and miss counters of the local cache, if caching is enabled.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.borrow().stats())
+    }
+
     /// Set the string value of a key.
     pub fn set<K: ToRedisArgs, V: ToRedisArgs>(&mut self, key: K, value: V) -> Response {
         let mut cmd = Cmd::new();
@@ -105,6 +362,24 @@ impl ClientHandle {
 
         self.call(cmd)
     }
+
+    /// Issue the command and convert the reply into `T`.
+    fn query<T: FromRedisValue + 'static>(&self, cmd: Cmd) -> Response<T> {
+        self.query_with(cmd, from_redis_value)
+    }
+
+    /// Issue the command and convert the reply using `f`, for replies that
+    /// need more context than `FromRedisValue` has.
+    fn query_with<T, F>(&self, cmd: Cmd, f: F) -> Response<T>
+        where T: 'static,
+              F: FnOnce(&Value) -> types::RedisResult<T> + 'static,
+    {
+        let ret = self.call(cmd).and_then(|val| {
+            f(&val).map_err(io::Error::from)
+        });
+
+        Box::new(ret)
+    }
 }
 
 impl Service for ClientHandle {
@@ -117,3 +392,77 @@ impl Service for ClientHandle {
         Box::new(self.inner.call(req))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use tokio_core::reactor::Core;
+
+    use super::*;
+
+    /// Read one command from the client as its arguments.
+    fn read_cmd<R: BufRead>(reader: &mut R) -> Option<Vec<String>> {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap() == 0 {
+            return None;
+        }
+        let n: usize = line.trim()[1..].parse().unwrap();
+
+        let mut args = vec![];
+        for _ in 0..n {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+            let len: usize = line.trim()[1..].parse().unwrap();
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).unwrap();
+            arg.truncate(len);
+            args.push(String::from_utf8(arg).unwrap());
+        }
+        Some(args)
+    }
+
+    #[test]
+    fn setinfo_error_keeps_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut cmds = vec![];
+            while let Some(args) = read_cmd(&mut reader) {
+                let reply: &[u8] = match (&args[0][..], args.get(1).map(|a| &a[..])) {
+                    ("CLIENT", Some("SETNAME")) => b"+OK\r\n",
+                    ("CLIENT", Some("SETINFO")) => b"-ERR unknown subcommand 'SETINFO'\r\n",
+                    _ => b"$3\r\nbar\r\n",
+                };
+                writer.write_all(reply).unwrap();
+                cmds.push(args);
+                if cmds.len() == 4 {
+                    break;
+                }
+            }
+            cmds
+        });
+
+        let mut core = Core::new().unwrap();
+        let ret = Client::new()
+            .name("test")
+            .lib_info("tokio-redis", "0.1.0")
+            .connect(&addr, &core.handle())
+            .and_then(|mut client| client.get("foo"));
+
+        assert_eq!(core.run(ret).unwrap(), Value::Data(b"bar".to_vec()));
+
+        let cmds = server.join().unwrap();
+        assert_eq!(cmds[0], vec!["CLIENT", "SETNAME", "test"]);
+        assert_eq!(cmds[1][1], "SETINFO");
+        assert_eq!(cmds[2][1], "SETINFO");
+        assert_eq!(cmds[3], vec!["GET", "foo"]);
+    }
+}
@@ -0,0 +1,214 @@
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::{Async, Future, Poll, Stream};
+use tokio_core::net::TcpStream;
+use tokio_core::reactor::Handle;
+
+use {Client, Cmd};
+use transport::{self, RedisTransport};
+use types::Value;
+
+/// A connection in monitor mode.
+///
+/// After `MONITOR` the server streams every command it processes to the
+/// connection, which cannot be used for anything else from then on.  The
+/// feed is exposed as a `Stream` of parsed events.  Note that monitoring
+/// noticeably reduces the throughput of the server.
+pub struct Monitor {
+    transport: RedisTransport<TcpStream>,
+}
+
+/// A command processed by the server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonitorEvent {
+    /// When the command was processed.
+    pub timestamp: SystemTime,
+    /// The database the command ran against.
+    pub db: u32,
+    /// The address of the client, or `lua` for commands run by scripts.
+    pub client_addr: String,
+    /// The command name and its arguments.
+    pub args: Vec<Vec<u8>>,
+}
+
+/// Connect a client in monitor mode.
+pub fn connect(client: Client, addr: &SocketAddr, handle: &Handle)
+        -> Box<Future<Item = Monitor, Error = io::Error>>
+{
+    let (setname, setinfo) = client.setup_cmds();
+
+    let mut monitor = Cmd::new();
+    monitor.arg("MONITOR");
+
+    // Only the feed is sent after `MONITOR`, so it has to go last
+    let ret = TcpStream::connect(addr, handle)
+        .and_then(move |io| transport::handshake(RedisTransport::new(io), setname, setinfo))
+        .and_then(move |transport| transport::handshake(transport, vec![monitor], vec![]))
+        .map(|transport| Monitor { transport: transport });
+
+    Box::new(ret)
+}
+
+impl Stream for Monitor {
+    type Item = MonitorEvent;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<MonitorEvent>, io::Error> {
+        loop {
+            let val = match try_ready!(self.transport.poll()) {
+                Some(val) => val,
+                None => return Ok(Async::Ready(None)),
+            };
+
+            let event = match val {
+                Value::Status(ref line) => MonitorEvent::parse(line),
+                _ => None,
+            };
+
+            match event {
+                Some(event) => return Ok(Async::Ready(Some(event))),
+                None => {
+                    trace!("skipping non-monitor reply; val={:?}", val);
+                }
+            }
+        }
+    }
+}
+
+impl MonitorEvent {
+    /// Parses a line of the monitor feed, which looks like
+    /// `1339518083.107412 [0 127.0.0.1:60866] "set" "key" "value"`.
+    pub fn parse(line: &str) -> Option<MonitorEvent> {
+        let mut parts = line.splitn(2, " [");
+        let timestamp = unwrap_or!(parts.next(), return None);
+        let rest = unwrap_or!(parts.next(), return None);
+
+        let mut parts = rest.splitn(2, "] ");
+        let client = unwrap_or!(parts.next(), return None);
+        let args = unwrap_or!(parts.next(), return None);
+
+        let mut parts = client.splitn(2, ' ');
+        let db = unwrap_or!(parts.next().and_then(|db| db.parse().ok()), return None);
+        let client_addr = unwrap_or!(parts.next(), return None);
+
+        Some(MonitorEvent {
+            timestamp: unwrap_or!(parse_timestamp(timestamp), return None),
+            db: db,
+            client_addr: client_addr.to_string(),
+            args: unwrap_or!(parse_args(args.as_bytes()), return None),
+        })
+    }
+}
+
+/// Parses `<secs>.<micros>`.
+fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let mut parts = s.splitn(2, '.');
+    let secs = unwrap_or!(parts.next().and_then(|secs| secs.parse().ok()), return None);
+    let micros = match parts.next() {
+        Some(micros) => unwrap_or!(micros.parse().ok(), return None),
+        None => 0,
+    };
+    Some(UNIX_EPOCH + Duration::from_secs(secs) + Duration::from_micros(micros))
+}
+
+/// Splits the quoted arguments of a monitor line, undoing the escaping
+/// the server applies to quotes, backslashes and non-printable bytes.
+fn parse_args(s: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut rv = vec![];
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] == b' ' {
+            i += 1;
+            continue;
+        }
+        if s[i] != b'"' {
+            return None;
+        }
+        i += 1;
+
+        let mut arg = vec![];
+        loop {
+            let c = unwrap_or!(s.get(i), return None);
+            i += 1;
+            match *c {
+                b'"' => break,
+                b'\\' => {
+                    let c = unwrap_or!(s.get(i), return None);
+                    i += 1;
+                    arg.push(match *c {
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        b'a' => 7,
+                        b'b' => 8,
+                        b'x' => {
+                            let hex = unwrap_or!(s.get(i..i + 2), return None);
+                            i += 2;
+                            let hex = unwrap_or!(::std::str::from_utf8(hex).ok(), return None);
+                            unwrap_or!(u8::from_str_radix(hex, 16).ok(), return None)
+                        }
+                        other => other,
+                    });
+                }
+                other => arg.push(other),
+            }
+        }
+        rv.push(arg);
+    }
+    Some(rv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&[u8]]) -> Vec<Vec<u8>> {
+        args.iter().map(|arg| arg.to_vec()).collect()
+    }
+
+    #[test]
+    fn parse_event() {
+        let line = r#"1339518083.107412 [3 127.0.0.1:60866] "set" "key" "value""#;
+        let event = MonitorEvent::parse(line).unwrap();
+        assert_eq!(event.timestamp, UNIX_EPOCH + Duration::new(1339518083, 107412000));
+        assert_eq!(event.db, 3);
+        assert_eq!(event.client_addr, "127.0.0.1:60866");
+        assert_eq!(event.args, args(&[b"set", b"key", b"value"]));
+    }
+
+    #[test]
+    fn parse_event_from_script() {
+        let event = MonitorEvent::parse(r#"1339518083.107412 [0 lua] "get" "k""#).unwrap();
+        assert_eq!(event.client_addr, "lua");
+        assert_eq!(event.args, args(&[b"get", b"k"]));
+    }
+
+    #[test]
+    fn parse_invalid_event() {
+        assert!(MonitorEvent::parse("OK").is_none());
+        assert!(MonitorEvent::parse(r#"abc [0 127.0.0.1:1] "ping""#).is_none());
+        assert!(MonitorEvent::parse(r#"1.5 [x 127.0.0.1:1] "ping""#).is_none());
+    }
+
+    #[test]
+    fn parse_escapes() {
+        let parsed = parse_args(br#""a\"b" "c\\d" "\n\r\t\a\b" "\x00\xff""#).unwrap();
+        assert_eq!(parsed, args(&[b"a\"b", b"c\\d", b"\n\r\t\x07\x08", b"\x00\xff"]));
+    }
+
+    #[test]
+    fn parse_empty_and_spaced_args() {
+        assert_eq!(parse_args(br#""" "a b""#).unwrap(), args(&[b"", b"a b"]));
+        assert_eq!(parse_args(b"").unwrap(), args(&[]));
+    }
+
+    #[test]
+    fn parse_invalid_args() {
+        assert!(parse_args(br#""unterminated"#).is_none());
+        assert!(parse_args(br#"unquoted"#).is_none());
+        assert!(parse_args(br#""\x4""#).is_none());
+        assert!(parse_args(br#""\xzz""#).is_none());
+    }
+}
@@ -3,6 +3,15 @@ use std::io::{self, Read, BufReader};
 use types::{RedisResult, RedisError, Value, ErrorKind, make_extension_error};
 
 
+/// A top level item read from a connection.
+pub enum Frame {
+    /// The reply to a command.
+    Reply(Value),
+    /// An out of band RESP3 push message such as a client side caching
+    /// invalidation.
+    Push(Vec<Value>),
+}
+
 /// The internal redis response parser.
 pub struct Parser<T> {
     reader: T,
@@ -28,19 +37,48 @@ impl<'a, T: Read> Parser<T> {
     /// values you can call this multiple times.  If the reader is not yet
     /// ready this will block.
     pub fn parse_value(&mut self) -> RedisResult<Value> {
+        let b = try!(self.read_byte());
+        self.parse_type(b)
+    }
+
+    /// parses a single top level item out of the stream.  This is like
+    /// `parse_value` but also accepts the out of band push messages that
+    /// RESP3 connections can receive in between replies.
+    pub fn parse_frame(&mut self) -> RedisResult<Frame> {
         let b = try!(self.read_byte());
+        match b as char {
+            '>' => {
+                match try!(self.parse_bulk()) {
+                    Value::Bulk(items) => Ok(Frame::Push(items)),
+                    _ => Ok(Frame::Push(vec![])),
+                }
+            }
+            _ => self.parse_type(b).map(Frame::Reply),
+        }
+    }
+
+    /* internal helpers */
+
+    fn parse_type(&mut self, b: u8) -> RedisResult<Value> {
         match b as char {
             '+' => self.parse_status(),
             ':' => self.parse_int(),
             '$' => self.parse_data(),
             '*' => self.parse_bulk(),
             '-' => self.parse_error(),
+            // RESP3 types are mapped onto the closest RESP2 value
+            '_' => self.parse_null(),
+            '#' => self.parse_boolean(),
+            ',' | '(' => self.parse_line_data(),
+            '=' => self.parse_verbatim(),
+            '!' => self.parse_blob_error(),
+            '%' => self.parse_map(),
+            '~' => self.parse_bulk(),
+            '|' => self.parse_attribute(),
             _ => fail!((ErrorKind::ResponseError, "Invalid response when parsing value")),
         }
     }
 
-    /* internal helpers */
-
     #[inline]
     fn expect_char(&mut self, refchar: char) -> RedisResult<()> {
         if try!(self.read_byte()) as char == refchar {
@@ -164,15 +202,80 @@ impl<'a, T: Read> Parser<T> {
         }
     }
 
+    fn parse_null(&mut self) -> RedisResult<Value> {
+        try!(self.read_line());
+        Ok(Value::Nil)
+    }
+
+    fn parse_boolean(&mut self) -> RedisResult<Value> {
+        match &try!(self.read_line())[..] {
+            b"t" => Ok(Value::Int(1)),
+            b"f" => Ok(Value::Int(0)),
+            _ => fail!((ErrorKind::ResponseError, "Expected boolean, got garbage")),
+        }
+    }
+
+    /// Doubles and big numbers are kept as data so they convert into
+    /// numbers and strings like bulk replies do.
+    fn parse_line_data(&mut self) -> RedisResult<Value> {
+        Ok(Value::Data(try!(self.read_line())))
+    }
+
+    fn parse_verbatim(&mut self) -> RedisResult<Value> {
+        match try!(self.parse_data()) {
+            // strip the `txt:` format prefix
+            Value::Data(ref data) if data.len() >= 4 => Ok(Value::Data(data[4..].to_vec())),
+            val => Ok(val),
+        }
+    }
+
+    /// Maps are flattened into a bulk of alternating keys and values which
+    /// is what the RESP2 replies of the same commands look like.
+    fn parse_map(&mut self) -> RedisResult<Value> {
+        let length = try!(self.read_int_line());
+        if length < 0 {
+            Ok(Value::Nil)
+        } else {
+            let mut rv = vec![];
+            rv.reserve(2 * length as usize);
+            for _ in 0..2 * length {
+                rv.push(try!(self.parse_value()));
+            }
+            Ok(Value::Bulk(rv))
+        }
+    }
+
+    /// Attributes carry auxiliary data ahead of the actual reply.  They
+    /// are skipped.
+    fn parse_attribute(&mut self) -> RedisResult<Value> {
+        try!(self.parse_map());
+        self.parse_value()
+    }
+
+    fn parse_blob_error(&mut self) -> RedisResult<Value> {
+        let length = try!(self.read_int_line());
+        let data = try!(self.read(length as usize));
+        try!(self.expect_newline());
+        match String::from_utf8(data) {
+            Ok(line) => self.make_error(line),
+            Err(_) => fail!((ErrorKind::ResponseError, "Expected valid string, got garbage")),
+        }
+    }
+
     fn parse_error(&mut self) -> RedisResult<Value> {
-        let desc = "An error was signalled by the server";
         let line = try!(self.read_string_line());
+        self.make_error(line)
+    }
+
+    fn make_error(&self, line: String) -> RedisResult<Value> {
+        let desc = "An error was signalled by the server";
         let mut pieces = line.splitn(2, ' ');
         let kind = match pieces.next().unwrap() {
             "ERR" => ErrorKind::ResponseError,
             "EXECABORT" => ErrorKind::ExecAbortError,
             "LOADING" => ErrorKind::BusyLoadingError,
             "NOSCRIPT" => ErrorKind::NoScriptError,
+            "NOPERM" => ErrorKind::NoPermission,
             code => { fail!(make_extension_error(code, pieces.next())); }
         };
         match pieces.next() {
@@ -191,3 +294,90 @@ pub fn parse_redis_value(bytes: &[u8]) -> RedisResult<Value> {
     let mut parser = Parser::new(BufReader::new(bytes));
     parser.parse_value()
 }
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use types::from_redis_value;
+
+    fn parse_frame(bytes: &[u8]) -> Frame {
+        Parser::new(BufReader::new(bytes)).parse_frame().unwrap()
+    }
+
+    #[test]
+    fn resp3_scalars() {
+        assert_eq!(parse_redis_value(b"_\r\n").unwrap(), Value::Nil);
+        assert_eq!(parse_redis_value(b"#t\r\n").unwrap(), Value::Int(1));
+        assert_eq!(parse_redis_value(b"#f\r\n").unwrap(), Value::Int(0));
+        assert_eq!(parse_redis_value(b",3.25\r\n").unwrap(), Value::Data(b"3.25".to_vec()));
+        assert_eq!(parse_redis_value(b"(12345678901234567890\r\n").unwrap(),
+                   Value::Data(b"12345678901234567890".to_vec()));
+        assert_eq!(parse_redis_value(b"=8\r\ntxt:some\r\n").unwrap(),
+                   Value::Data(b"some".to_vec()));
+        assert!(parse_redis_value(b"#x\r\n").is_err());
+    }
+
+    #[test]
+    fn resp3_aggregates() {
+        assert_eq!(parse_redis_value(b"%1\r\n+key\r\n:1\r\n").unwrap(),
+                   Value::Bulk(vec![Value::Status("key".to_string()), Value::Int(1)]));
+        assert_eq!(parse_redis_value(b"~2\r\n:1\r\n:2\r\n").unwrap(),
+                   Value::Bulk(vec![Value::Int(1), Value::Int(2)]));
+        // Attributes are skipped in favor of the reply that follows them
+        assert_eq!(parse_redis_value(b"|1\r\n+ttl\r\n:3\r\n:42\r\n").unwrap(),
+                   Value::Int(42));
+    }
+
+    #[test]
+    fn resp3_errors() {
+        let err = parse_redis_value(b"!18\r\nNOPERM not for you\r\n").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NoPermission);
+        let err = parse_redis_value(b"-ERR unknown command\r\n").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ResponseError);
+    }
+
+    #[test]
+    fn push_frames() {
+        match parse_frame(b">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nfoo\r\n") {
+            Frame::Push(items) => {
+                assert_eq!(items, vec![
+                    Value::Data(b"invalidate".to_vec()),
+                    Value::Bulk(vec![Value::Data(b"foo".to_vec())]),
+                ]);
+            }
+            Frame::Reply(_) => panic!("expected a push frame"),
+        }
+
+        match parse_frame(b":1\r\n") {
+            Frame::Reply(value) => assert_eq!(value, Value::Int(1)),
+            Frame::Push(_) => panic!("expected a reply"),
+        }
+    }
+
+    #[test]
+    fn nested_pairs() {
+        let bytes = b"*2\r\n*2\r\n$1\r\na\r\n,1.5\r\n*2\r\n$1\r\nb\r\n,2\r\n";
+        let value = parse_redis_value(bytes).unwrap();
+        let pairs: Vec<(String, f64)> = from_redis_value(&value).unwrap();
+        assert_eq!(pairs, vec![("a".to_string(), 1.5), ("b".to_string(), 2.0)]);
+
+        // The RESP2 form of the same reply is flat
+        let bytes = b"*4\r\n$1\r\na\r\n$3\r\n1.5\r\n$1\r\nb\r\n$1\r\n2\r\n";
+        let value = parse_redis_value(bytes).unwrap();
+        let flat: Vec<(String, f64)> = from_redis_value(&value).unwrap();
+        assert_eq!(flat, pairs);
+    }
+
+    #[test]
+    fn nested_pairs_into_map() {
+        let bytes = b"*2\r\n*2\r\n$1\r\na\r\n$1\r\n1\r\n*2\r\n$1\r\nb\r\n$1\r\n2\r\n";
+        let value = parse_redis_value(bytes).unwrap();
+        let map: HashMap<String, i64> = from_redis_value(&value).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["b"], 2);
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+
+use futures::{future, Async, Future, Poll, Sink, Stream};
+use tokio_core::net::TcpStream;
+use tokio_core::reactor::Handle;
+
+use {Client, Cmd};
+use transport::{self, RedisTransport};
+use types::{ToRedisArgs, FromRedisValue, Value, RedisResult, from_redis_value};
+
+/// A connection in subscriber mode.
+///
+/// Once a connection subscribed to a channel redis only pushes messages
+/// to it, so it cannot be multiplexed with regular requests.  A `PubSub`
+/// owns its socket and yields the messages for all subscriptions as a
+/// `Stream`.  Subscription confirmations are consumed internally.
+pub struct PubSub {
+    transport: RedisTransport<TcpStream>,
+    // Messages received while waiting for subscription confirmations
+    pending: VecDeque<Msg>,
+}
+
+/// A future resolving to the `PubSub` connection once a command was
+/// written to the socket, or for subscriptions once they were confirmed.
+pub type PubSubFuture = Box<Future<Item = PubSub, Error = io::Error>>;
+
+/// A message received on a subscribed channel.
+///
+/// Channel names and patterns are binary safe, so they are kept as
+/// bytes.
+#[derive(Clone, Debug)]
+pub struct Msg {
+    channel: Vec<u8>,
+    pattern: Option<Vec<u8>>,
+    payload: Value,
+}
+
+/// Waits for the confirmations of a subscription command.
+struct Confirm {
+    pubsub: Option<PubSub>,
+    kind: &'static str,
+    remaining: usize,
+}
+
+/// Connect a client in subscriber mode.  The connection is named as
+/// configured on `client` before it is handed out.
+pub fn connect(client: Client, addr: &SocketAddr, handle: &Handle) -> PubSubFuture {
+    let (setname, setinfo) = client.setup_cmds();
+
+    let ret = TcpStream::connect(addr, handle)
+        .and_then(move |io| transport::handshake(RedisTransport::new(io), setname, setinfo))
+        .map(PubSub::from_transport);
+
+    Box::new(ret)
+}
+
+impl PubSub {
+    /// Wraps an already connected socket.
+    pub fn new(io: TcpStream) -> PubSub {
+        PubSub::from_transport(RedisTransport::new(io))
+    }
+
+    fn from_transport(transport: RedisTransport<TcpStream>) -> PubSub {
+        PubSub {
+            transport: transport,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Subscribe to one or more channels.  Resolves once the server
+    /// confirmed every subscription, messages received in the meantime
+    /// are kept for the stream.
+    pub fn subscribe<C: ToRedisArgs>(self, channels: C) -> PubSubFuture {
+        self.subscribe_with("SUBSCRIBE", "subscribe", channels)
+    }
+
+    /// Subscribe to all channels matching one or more glob-style patterns.
+    /// Resolves once the server confirmed every subscription.
+    pub fn psubscribe<P: ToRedisArgs>(self, patterns: P) -> PubSubFuture {
+        self.subscribe_with("PSUBSCRIBE", "psubscribe", patterns)
+    }
+
+    /// Subscribe to one or more shard channels.  Resolves once the server
+    /// confirmed every subscription.
+    pub fn ssubscribe<C: ToRedisArgs>(self, channels: C) -> PubSubFuture {
+        self.subscribe_with("SSUBSCRIBE", "ssubscribe", channels)
+    }
+
+    fn subscribe_with<A: ToRedisArgs>(self, name: &str, kind: &'static str, args: A)
+            -> PubSubFuture
+    {
+        let args = args.to_redis_args();
+        if args.is_empty() {
+            return Box::new(future::err(io::Error::new(io::ErrorKind::InvalidInput,
+                                                       "nothing to subscribe to")));
+        }
+        let remaining = args.len();
+
+        let ret = self.send(name, args).and_then(move |pubsub| {
+            Confirm {
+                pubsub: Some(pubsub),
+                kind: kind,
+                remaining: remaining,
+            }
+        });
+
+        Box::new(ret)
+    }
+
+    /// Unsubscribe from the given channels, or from all channels if
+    /// `channels` produces no arguments.
+    pub fn unsubscribe<C: ToRedisArgs>(self, channels: C) -> PubSubFuture {
+        self.send("UNSUBSCRIBE", channels)
+    }
+
+    /// Unsubscribe from the given patterns, or from all patterns if
+    /// `patterns` produces no arguments.
+    pub fn punsubscribe<P: ToRedisArgs>(self, patterns: P) -> PubSubFuture {
+        self.send("PUNSUBSCRIBE", patterns)
+    }
+
+    /// Unsubscribe from the given shard channels, or from all shard
+    /// channels if `channels` produces no arguments.
+    pub fn sunsubscribe<C: ToRedisArgs>(self, channels: C) -> PubSubFuture {
+        self.send("SUNSUBSCRIBE", channels)
+    }
+
+    fn send<A: ToRedisArgs>(self, name: &str, args: A) -> PubSubFuture {
+        let mut cmd = Cmd::new();
+        cmd.arg(name).arg(args);
+
+        self.send_cmd(cmd)
+    }
+
+    /// Write a raw command to the connection.  Replies to it arrive on
+    /// the message stream where they are skipped.
+    pub fn send_cmd(self, cmd: Cmd) -> PubSubFuture {
+        let pending = self.pending;
+        let ret = self.transport.send(cmd)
+            .map(move |transport| PubSub { transport: transport, pending: pending });
+
+        Box::new(ret)
+    }
+
+    /// Run a regular command and wait for its reply, failing if the
+    /// server replied with an error.  This is only possible before the
+    /// first subscription, subscribed connections only accept the
+    /// subscription commands.
+    pub fn run_cmd(self, cmd: Cmd) -> PubSubFuture {
+        let pending = self.pending;
+        let ret = transport::handshake(self.transport, vec![cmd], vec![])
+            .map(move |transport| PubSub { transport: transport, pending: pending });
+
+        Box::new(ret)
+    }
+}
+
+impl Future for Confirm {
+    type Item = PubSub;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<PubSub, io::Error> {
+        while self.remaining > 0 {
+            let val = {
+                let pubsub = self.pubsub.as_mut().expect("poll after completion");
+                match try_ready!(pubsub.transport.poll()) {
+                    Some(val) => val,
+                    None => {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                                  "connection closed before subscribing"));
+                    }
+                }
+            };
+
+            if is_confirmation(&val, self.kind) {
+                self.remaining -= 1;
+            } else if let Some(msg) = Msg::from_value(&val) {
+                self.pubsub.as_mut().unwrap().pending.push_back(msg);
+            } else {
+                trace!("skipping non-message reply; val={:?}", val);
+            }
+        }
+
+        Ok(Async::Ready(self.pubsub.take().expect("poll after completion")))
+    }
+}
+
+/// Whether a pushed value confirms a subscription of the given kind.
+fn is_confirmation(value: &Value, kind: &str) -> bool {
+    match *value {
+        Value::Bulk(ref items) if items.len() == 3 => match items[0] {
+            Value::Data(ref name) => &name[..] == kind.as_bytes(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+impl Stream for PubSub {
+    type Item = Msg;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Msg>, io::Error> {
+        if let Some(msg) = self.pending.pop_front() {
+            return Ok(Async::Ready(Some(msg)));
+        }
+
+        loop {
+            let val = match try_ready!(self.transport.poll()) {
+                Some(val) => val,
+                None => return Ok(Async::Ready(None)),
+            };
+
+            match Msg::from_value(&val) {
+                Some(msg) => return Ok(Async::Ready(Some(msg))),
+                None => {
+                    trace!("skipping non-message reply; val={:?}", val);
+                }
+            }
+        }
+    }
+}
+
+impl Msg {
+    /// Tries to convert a pushed value into a message.  Returns `None`
+    /// for anything that is not a `message`, `pmessage` or `smessage`
+    /// push, such as subscription confirmations.
+    pub fn from_value(value: &Value) -> Option<Msg> {
+        let items = match *value {
+            Value::Bulk(ref items) => items,
+            _ => return None,
+        };
+        let kind: String = unwrap_or!(items.get(0).and_then(|v| from_redis_value(v).ok()),
+                                      return None);
+
+        let (pattern, channel, payload) = match (&kind[..], items.len()) {
+            ("message", 3) | ("smessage", 3) => (None, &items[1], &items[2]),
+            ("pmessage", 4) => (Some(&items[1]), &items[2], &items[3]),
+            _ => return None,
+        };
+
+        let pattern = match pattern {
+            Some(&Value::Data(ref pattern)) => Some(pattern.clone()),
+            Some(_) => return None,
+            None => None,
+        };
+        let channel = match *channel {
+            Value::Data(ref channel) => channel.clone(),
+            _ => return None,
+        };
+
+        Some(Msg {
+            channel: channel,
+            pattern: pattern,
+            payload: payload.clone(),
+        })
+    }
+
+    /// The channel the message was published to.
+    pub fn channel(&self) -> &[u8] {
+        &self.channel
+    }
+
+    /// The pattern that matched the channel if the message was received
+    /// through a pattern subscription.
+    pub fn pattern(&self) -> Option<&[u8]> {
+        self.pattern.as_ref().map(|p| &p[..])
+    }
+
+    /// Converts the payload into the given type.
+    pub fn payload<T: FromRedisValue>(&self) -> RedisResult<T> {
+        from_redis_value(&self.payload)
+    }
+
+    /// The raw payload value.
+    pub fn payload_value(&self) -> &Value {
+        &self.payload
+    }
+}
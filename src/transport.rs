@@ -1,8 +1,10 @@
 use {Cmd, Value};
-use parser::Parser;
+use parser::{Frame, Parser};
 use types::RedisError;
 use tokio_io::{AsyncRead, AsyncWrite};
-use futures::{Async, AsyncSink, Poll, Stream, Sink, StartSend};
+use futures::{future, stream, Async, AsyncSink, Future, Poll, Stream, Sink, StartSend};
+use futures::future::Loop;
+use futures::sync::mpsc::UnboundedSender;
 use std::mem;
 use std::io::{self, Cursor};
 
@@ -18,6 +20,8 @@ pub struct RedisTransport<T> {
     wr: io::Cursor<Vec<u8>>,
     // Queued commands
     cmds: Vec<Cmd>,
+    // Receives RESP3 push messages, they are dropped if not set
+    push: Option<UnboundedSender<Vec<Value>>>,
 }
 
 struct RedisProto;
@@ -35,13 +39,41 @@ impl<T> RedisTransport<T>
             rd: vec![],
             wr: io::Cursor::new(vec![]),
             cmds: vec![],
+            push: None,
         }
     }
+
+    /// Like `new` but forwards push messages to `push` instead of
+    /// dropping them.
+    pub fn with_push(inner: T, push: UnboundedSender<Vec<Value>>) -> RedisTransport<T> {
+        let mut transport = RedisTransport::new(inner);
+        transport.push = Some(push);
+        transport
+    }
 }
 
 impl<T> RedisTransport<T>
     where T: AsyncRead + AsyncWrite,
 {
+    fn consume(&mut self, pos: usize) {
+        // Data is consumed
+        let tail = self.rd.split_off(pos);
+        mem::replace(&mut self.rd, tail);
+    }
+
+    fn dispatch_push(&mut self, items: Vec<Value>) {
+        match self.push {
+            Some(ref tx) => {
+                if tx.unbounded_send(items).is_err() {
+                    trace!("push receiver gone; dropping message");
+                }
+            }
+            None => {
+                trace!("dropping push message; items={:?}", items);
+            }
+        }
+    }
+
     fn wr_is_empty(&self) -> bool {
         self.wr_remaining() == 0
     }
@@ -115,32 +147,37 @@ impl<T> Stream for RedisTransport<T>
             }
         }
 
-        // Try to parse some data!
-        let pos;
-        let ret = {
-            let mut cursor = Cursor::new(&self.rd);
+        loop {
+            // Try to parse some data!
+            let pos;
             let res = {
-                let mut parser = Parser::new(&mut cursor);
-                parser.parse_value()
+                let mut cursor = Cursor::new(&self.rd);
+                let res = {
+                    let mut parser = Parser::new(&mut cursor);
+                    parser.parse_frame()
+                };
+                pos = cursor.position() as usize;
+                res
             };
-            pos = cursor.position() as usize;
 
-            match res {
-                Ok(val) => Ok(Async::Ready(Some(val))),
+            let ret = match res {
+                Ok(Frame::Reply(val)) => Ok(Async::Ready(Some(val))),
+                Ok(Frame::Push(items)) => {
+                    // Push messages are not replies, keep looking for one
+                    self.consume(pos);
+                    self.dispatch_push(items);
+                    continue;
+                }
                 Err(e) => e.into(),
-            }
-        };
+            };
 
-        match ret {
-            Ok(Async::NotReady) => {},
-            _ => {
-                // Data is consumed
-                let tail = self.rd.split_off(pos);
-                mem::replace(&mut self.rd, tail);
+            match ret {
+                Ok(Async::NotReady) => {},
+                _ => self.consume(pos),
             }
-        }
 
-        ret
+            return ret;
+        }
     }
 }
 
@@ -181,3 +218,42 @@ impl<T> Sink for RedisTransport<T>
         }
     }
 }
+
+/// Runs commands on a connection that is not driven by a `ClientHandle`
+/// and waits for their replies, so that the connection can be handed out
+/// with nothing left to read.  Errors replied to `required` commands fail
+/// the future, those replied to `optional` ones are ignored.
+pub fn handshake<T>(transport: RedisTransport<T>, required: Vec<Cmd>, optional: Vec<Cmd>)
+        -> Box<Future<Item = RedisTransport<T>, Error = io::Error>>
+    where T: AsyncRead + AsyncWrite + 'static,
+{
+    let num_required = required.len();
+    let total = num_required + optional.len();
+    let cmds = stream::iter_ok::<_, io::Error>(required.into_iter().chain(optional));
+
+    let ret = transport.send_all(cmds).and_then(move |(transport, _)| {
+        future::loop_fn((transport, 0), move |(transport, idx)| {
+            let ret: Box<Future<Item = _, Error = io::Error>> = if idx == total {
+                Box::new(future::ok(Loop::Break(transport)))
+            } else {
+                Box::new(transport.into_future().then(move |res| match res {
+                    Ok((Some(_), transport)) => Ok(Loop::Continue((transport, idx + 1))),
+                    Ok((None, _)) => {
+                        Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                           "connection closed during handshake"))
+                    }
+                    Err((e, transport)) => {
+                        if idx < num_required {
+                            return Err(e);
+                        }
+                        trace!("ignoring handshake error; err={:?}", e);
+                        Ok(Loop::Continue((transport, idx + 1)))
+                    }
+                }))
+            };
+            ret
+        })
+    });
+
+    Box::new(ret)
+}
@@ -33,6 +33,9 @@ pub enum ErrorKind {
     BusyLoadingError,
     /// A script that was requested does not actually exist.
     NoScriptError,
+    /// The ACL user is not allowed to run the command or to access the
+    /// keys or channels it uses.
+    NoPermission,
     /// An error that was caused because the parameter to the
     /// client were wrong.
     InvalidClientConfig,
@@ -192,6 +195,15 @@ impl From<io::Error> for RedisError {
     }
 }
 
+impl From<RedisError> for io::Error {
+    fn from(err: RedisError) -> io::Error {
+        match err.repr {
+            ErrorRepr::IoError(e) => e,
+            repr => io::Error::new(io::ErrorKind::Other, RedisError { repr: repr }),
+        }
+    }
+}
+
 impl From<Utf8Error> for RedisError {
     fn from(_: Utf8Error) -> RedisError {
         RedisError { repr: ErrorRepr::WithDescription(ErrorKind::TypeError, "Invalid UTF-8") }
@@ -281,6 +293,7 @@ impl RedisError {
             ErrorKind::ExecAbortError => "script execution aborted",
             ErrorKind::BusyLoadingError => "busy loading",
             ErrorKind::NoScriptError => "no script",
+            ErrorKind::NoPermission => "no permission",
             ErrorKind::InvalidClientConfig => "invalid client config",
             ErrorKind::IoError => "I/O error",
             ErrorKind::ExtensionError => "extension error",
@@ -359,6 +372,7 @@ pub type RedisResult<T> = Result<T, RedisError>;
 #[derive(Debug)]
 pub struct InfoDict {
     map: HashMap<String, Value>,
+    sections: Vec<(String, Vec<String>)>,
 }
 
 /// This type provides convenient access to key/value data returned by
@@ -372,19 +386,31 @@ impl InfoDict {
     /// Creates a new info dictionary from a string in the response of
     /// the INFO command.  Each line is a key, value pair with the
     /// key and value separated by a colon (`:`).  Lines starting with a
-    /// hash (`#`) are ignored.
+    /// hash (`#`) start a new section, keys before the first of them
+    /// belong to a section with an empty name.
     pub fn new(kvpairs: &str) -> InfoDict {
         let mut map = HashMap::new();
+        let mut sections: Vec<(String, Vec<String>)> = vec![];
         for line in kvpairs.lines() {
-            if line.len() == 0 || line.starts_with("#") {
+            if line.len() == 0 {
+                continue;
+            }
+            if line.starts_with("#") {
+                sections.push((line[1..].trim().to_string(), vec![]));
                 continue;
             }
             let mut p = line.splitn(2, ':');
             let k = unwrap_or!(p.next(), continue).to_string();
             let v = unwrap_or!(p.next(), continue).to_string();
+            if sections.is_empty() {
+                sections.push((String::new(), vec![]));
+            }
+            if let Some(&mut (_, ref mut keys)) = sections.last_mut() {
+                keys.push(k.clone());
+            }
             map.insert(k, Value::Status(v));
         }
-        InfoDict { map: map }
+        InfoDict { map: map, sections: sections }
     }
 
     /// Fetches a value by key and converts it into the given type.
@@ -407,6 +433,155 @@ impl InfoDict {
     pub fn len(&self) -> usize {
         self.map.len()
     }
+
+    /// Returns the names of the sections in the order the server sent
+    /// them, for instance `Server` or `Keyspace`.
+    pub fn sections(&self) -> Vec<&str> {
+        self.sections.iter().map(|&(ref name, _)| &name[..]).collect()
+    }
+
+    /// Returns the keys and values of a section in the order the server
+    /// sent them.  Section names are compared case insensitively.
+    pub fn section(&self, name: &str) -> Vec<(&str, &Value)> {
+        let mut rv = vec![];
+        for &(ref section, ref keys) in self.sections.iter() {
+            if !section.eq_ignore_ascii_case(name) {
+                continue;
+            }
+            for key in keys.iter() {
+                if let Some(value) = self.map.get(key) {
+                    rv.push((&key[..], value));
+                }
+            }
+        }
+        rv
+    }
+
+    /// Returns the name of the section a key belongs to.
+    pub fn section_of(&self, key: &str) -> Option<&str> {
+        self.sections.iter()
+            .find(|&&(_, ref keys)| keys.iter().any(|k| k == key))
+            .map(|&(ref name, _)| &name[..])
+    }
+
+    /// Parses the `db<n>` lines of the keyspace section.
+    pub fn keyspace(&self) -> Vec<KeyspaceInfo> {
+        self.section("keyspace").into_iter().filter_map(|(key, value)| {
+            let db = unwrap_or!(prefixed_index(key, "db"), return None);
+            let fields = unwrap_or!(compound_fields(value), return None);
+            Some(KeyspaceInfo {
+                db: db,
+                keys: unwrap_or!(parse_field(&fields, "keys"), return None),
+                expires: unwrap_or!(parse_field(&fields, "expires"), return None),
+                avg_ttl: parse_field(&fields, "avg_ttl").unwrap_or(0),
+            })
+        }).collect()
+    }
+
+    /// Parses the `slave<n>` lines of the replication section.
+    pub fn replicas(&self) -> Vec<ReplicaInfo> {
+        self.section("replication").into_iter().filter_map(|(key, value)| {
+            let index = unwrap_or!(prefixed_index(key, "slave"), return None);
+            let fields = unwrap_or!(compound_fields(value), return None);
+            Some(ReplicaInfo {
+                index: index,
+                ip: unwrap_or!(fields.get("ip"), return None).to_string(),
+                port: unwrap_or!(parse_field(&fields, "port"), return None),
+                state: fields.get("state").map(|s| s.to_string()).unwrap_or_default(),
+                offset: parse_field(&fields, "offset").unwrap_or(0),
+                lag: parse_field(&fields, "lag").unwrap_or(0),
+            })
+        }).collect()
+    }
+
+    /// Parses the `cmdstat_<command>` lines of the commandstats section.
+    pub fn command_stats(&self) -> Vec<CommandStats> {
+        self.section("commandstats").into_iter().filter_map(|(key, value)| {
+            if !key.starts_with("cmdstat_") {
+                return None;
+            }
+            let fields = unwrap_or!(compound_fields(value), return None);
+            Some(CommandStats {
+                command: key["cmdstat_".len()..].to_string(),
+                calls: unwrap_or!(parse_field(&fields, "calls"), return None),
+                usec: parse_field(&fields, "usec").unwrap_or(0),
+                usec_per_call: parse_field(&fields, "usec_per_call").unwrap_or(0.0),
+                rejected_calls: parse_field(&fields, "rejected_calls").unwrap_or(0),
+                failed_calls: parse_field(&fields, "failed_calls").unwrap_or(0),
+            })
+        }).collect()
+    }
+}
+
+/// A database as reported in the keyspace section of `INFO`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct KeyspaceInfo {
+    pub db: u32,
+    pub keys: u64,
+    /// The number of keys with an expiration.
+    pub expires: u64,
+    /// The average time to live in milliseconds of the keys with an
+    /// expiration.
+    pub avg_ttl: u64,
+}
+
+/// A replica as reported in the replication section of `INFO`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ReplicaInfo {
+    /// The `n` of the `slave<n>` key.
+    pub index: u32,
+    pub ip: String,
+    pub port: u16,
+    /// The replication state, such as `online` or `wait_bgsave`.
+    pub state: String,
+    /// The replication offset acknowledged by the replica.
+    pub offset: i64,
+    /// Seconds since the last acknowledgement of the replica.
+    pub lag: i64,
+}
+
+/// The statistics of a command as reported in the commandstats section of
+/// `INFO`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct CommandStats {
+    /// The lowercase command name, subcommands are joined with a `|`.
+    pub command: String,
+    pub calls: u64,
+    /// The total CPU time spent in microseconds.
+    pub usec: u64,
+    pub usec_per_call: f64,
+    /// Calls rejected before being executed, for instance because of wrong
+    /// arguments.
+    pub rejected_calls: u64,
+    /// Calls that failed while being executed.
+    pub failed_calls: u64,
+}
+
+fn prefixed_index(key: &str, prefix: &str) -> Option<u32> {
+    if !key.starts_with(prefix) {
+        return None;
+    }
+    key[prefix.len()..].parse().ok()
+}
+
+/// Splits a compound value of the form `a=1,b=2` into its fields.
+fn compound_fields(value: &Value) -> Option<HashMap<&str, &str>> {
+    let s = match *value {
+        Value::Status(ref s) => s,
+        _ => return None,
+    };
+    let mut rv = HashMap::new();
+    for field in s.split(',') {
+        let mut p = field.splitn(2, '=');
+        let k = unwrap_or!(p.next(), continue);
+        let v = unwrap_or!(p.next(), continue);
+        rv.insert(k, v);
+    }
+    Some(rv)
+}
+
+fn parse_field<T: ::std::str::FromStr>(fields: &HashMap<&str, &str>, name: &str) -> Option<T> {
+    fields.get(name).and_then(|v| v.parse().ok())
 }
 
 
@@ -789,6 +964,18 @@ impl<K: FromRedisValue + Eq + Hash, V: FromRedisValue> FromRedisValue for HashMa
         match *v {
             Value::Bulk(ref items) => {
                 let mut rv = HashMap::new();
+                // RESP3 replies may nest the pairs instead of sending
+                // them flat
+                let nested = !items.is_empty() && items.iter().all(|item| match *item {
+                    Value::Bulk(ref pair) => pair.len() == 2,
+                    _ => false,
+                });
+                if nested {
+                    for (k, v) in try!(from_redis_value::<Vec<(K, V)>>(v)) {
+                        rv.insert(k, v);
+                    }
+                    return Ok(rv);
+                }
                 let mut iter = items.iter();
                 loop {
                     let k = unwrap_or!(iter.next(), break);
@@ -866,6 +1053,20 @@ macro_rules! from_redis_value_for_tuple {
                 // hacky way to count the tuple size
                 let mut n = 0;
                 $(let $name = (); n += 1;)*
+                // RESP3 replies nest the tuples instead of sending them
+                // flat, as in `[[member, score], ...]`.
+                let nested = n > 1 && !items.is_empty() && items.iter().all(|item| match *item {
+                    Value::Bulk(ref tuple) => tuple.len() == n,
+                    _ => false,
+                });
+                if nested {
+                    let mut rv = vec![];
+                    for item in items.iter() {
+                        rv.push(try!(from_redis_value(item)));
+                    }
+                    return Ok(rv);
+                }
+
                 if items.len() % n != 0 {
                     invalid_type_error!(items, "Bulk response of wrong dimension")
                 }
@@ -921,3 +1122,84 @@ impl<T: FromRedisValue> FromRedisValue for Option<T> {
 pub fn from_redis_value<T: FromRedisValue>(v: &Value) -> RedisResult<T> {
     FromRedisValue::from_redis_value(v)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO: &'static str = "\
+# Server\r
+redis_version:7.2.4\r
+tcp_port:6379\r
+\r
+# Replication\r
+role:master\r
+connected_slaves:2\r
+slave0:ip=10.0.0.2,port=6380,state=online,offset=1234,lag=0\r
+slave1:ip=10.0.0.3,port=6381,state=wait_bgsave,offset=0,lag=1\r
+master_repl_offset:1234\r
+\r
+# Commandstats\r
+cmdstat_get:calls=21,usec=175,usec_per_call=8.33,rejected_calls=0,failed_calls=1\r
+cmdstat_set:calls=3,usec=40,usec_per_call=13.33,rejected_calls=2,failed_calls=0\r
+\r
+# Keyspace\r
+db0:keys=5,expires=1,avg_ttl=3000\r
+db12:keys=1,expires=0,avg_ttl=0\r
+";
+
+    #[test]
+    fn sections() {
+        let info = InfoDict::new(INFO);
+        assert_eq!(info.sections(), vec!["Server", "Replication", "Commandstats", "Keyspace"]);
+        assert_eq!(info.section_of("tcp_port"), Some("Server"));
+        assert_eq!(info.section_of("unknown"), None);
+        assert_eq!(info.get::<u16>("tcp_port"), Some(6379));
+
+        let server: Vec<&str> = info.section("server").into_iter().map(|(k, _)| k).collect();
+        assert_eq!(server, vec!["redis_version", "tcp_port"]);
+    }
+
+    #[test]
+    fn keys_before_first_section() {
+        let info = InfoDict::new("used_memory:1024\r\n");
+        assert_eq!(info.sections(), vec![""]);
+        assert_eq!(info.get::<u64>("used_memory"), Some(1024));
+    }
+
+    #[test]
+    fn keyspace() {
+        let info = InfoDict::new(INFO);
+        assert_eq!(info.keyspace(), vec![
+            KeyspaceInfo { db: 0, keys: 5, expires: 1, avg_ttl: 3000 },
+            KeyspaceInfo { db: 12, keys: 1, expires: 0, avg_ttl: 0 },
+        ]);
+    }
+
+    #[test]
+    fn replicas() {
+        let info = InfoDict::new(INFO);
+        let replicas = info.replicas();
+        assert_eq!(replicas.len(), 2);
+        assert_eq!(replicas[0].index, 0);
+        assert_eq!(replicas[0].ip, "10.0.0.2");
+        assert_eq!(replicas[0].port, 6380);
+        assert_eq!(replicas[0].state, "online");
+        assert_eq!(replicas[0].offset, 1234);
+        assert_eq!(replicas[1].state, "wait_bgsave");
+        assert_eq!(replicas[1].lag, 1);
+    }
+
+    #[test]
+    fn command_stats() {
+        let info = InfoDict::new(INFO);
+        let stats = info.command_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].command, "get");
+        assert_eq!(stats[0].calls, 21);
+        assert_eq!(stats[0].usec, 175);
+        assert_eq!(stats[0].usec_per_call, 8.33);
+        assert_eq!(stats[0].failed_calls, 1);
+        assert_eq!(stats[1].rejected_calls, 2);
+    }
+}